@@ -3,7 +3,10 @@ use common::*;
 
 use colour::*;
 use crossbeam_channel::unbounded;
-use skakarlak::search::Searcher;
+use skakarlak::search::{
+    params::{InternalIterative, SearchParams},
+    Searcher,
+};
 use skakarlak::{SearchCommand, SearchControl, SearchInfo};
 use std::io::Write;
 use std::thread;
@@ -66,11 +69,11 @@ const POSITIONS: &[&'static str] = &[
     "3k4/p1p5/3p4/pP2p2P/3N4/1bK3P1/3r3P/8 w - - 34 19",
 ];
 
-fn search(depth: u8) -> Vec<Measurements> {
+fn search(depth: u8, params: SearchParams) -> Vec<Measurements> {
     let (cmd_tx, cmd_rx) = unbounded();
     let (info_tx, info_rx) = unbounded();
 
-    thread::spawn(|| Searcher::new(cmd_rx, info_tx).run());
+    thread::spawn(|| Searcher::with_params(cmd_rx, info_tx, params).run());
 
     white_bold!("\nSearching positions");
     black_ln!(" to depth: {}\n", depth);
@@ -132,8 +135,31 @@ fn search(depth: u8) -> Vec<Measurements> {
 }
 
 fn main() {
-    let results = search(7);
+    // Compare the ways of handling nodes without a TT move when asked to, otherwise measure the
+    // throughput of the default search
+    if !std::env::args().any(|arg| arg == "--internal-iterative") {
+        summarize(search(7, SearchParams::default()));
+        return;
+    }
+
+    for (name, internal_iterative) in [
+        ("No internal iteration", InternalIterative::Disabled),
+        ("Internal iterative deepening", InternalIterative::Deepening),
+        (
+            "Internal iterative reductions",
+            InternalIterative::Reductions,
+        ),
+    ] {
+        white_ln_bold!("\n{}", name);
+        let params = SearchParams {
+            internal_iterative,
+            ..SearchParams::default()
+        };
+        summarize(search(7, params));
+    }
+}
 
+fn summarize(results: Vec<Measurements>) {
     // Extract values
     let mut nodes = results.iter().map(|m| m.nodes).collect::<Vec<_>>();
     nodes.sort();
@@ -154,4 +180,10 @@ fn main() {
     plot_quantity("Nodes", nodes);
     plot_quantity("Time [ms]", times);
     plot_quantity("Throughput [kN/s]", k_nodes_per_sec);
+
+    // Throughput over all positions, dominated by the largest searches
+    let total_nodes = results.iter().map(|m| m.nodes).sum::<u64>();
+    let total_ms = results.iter().map(|m| m.time_ms).sum::<u128>();
+    blue_ln_bold!("    Total throughput [kN/s] ");
+    white_ln_bold!("    {:.1}", total_nodes as f64 / total_ms as f64);
 }
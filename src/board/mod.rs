@@ -0,0 +1,523 @@
+pub mod movegen;
+
+use crate::eval::accumulator::Accumulator;
+use shakmaty::{
+    attacks,
+    zobrist::{Zobrist64, ZobristValue},
+    Bitboard, CastlingMode, CastlingSide, Chess, Color, EnPassantMode, File, FromSetup, Move,
+    Piece, Position, Rank, Role, Setup, Square,
+};
+use std::num::NonZeroU32;
+
+/// State that cannot be recovered from the move alone, saved to undo the move.
+#[derive(Clone, Copy, Debug)]
+struct Undo {
+    // Move played, or none for a null move
+    mv: Option<Move>,
+    castling_rights: Bitboard,
+    ep_square: Option<Square>,
+    halfmoves: u32,
+    fullmoves: u32,
+    hash: Zobrist64,
+    pawn_hash: Zobrist64,
+    checkers: Bitboard,
+    accumulator: Accumulator,
+}
+
+/// Chess position that is changed in place by playing and undoing moves, keeping its Zobrist hash
+/// up to date, so the search never has to copy positions or hash them from scratch.
+#[derive(Clone, Debug)]
+pub struct Board {
+    board: shakmaty::Board,
+    turn: Color,
+    // Squares of the rooks that can still castle
+    castling_rights: Bitboard,
+    // Target square of an en passant capture, only set if the capture is legal
+    ep_square: Option<Square>,
+    halfmoves: u32,
+    fullmoves: u32,
+    // Hash of the position as `Position::zobrist_hash` with `EnPassantMode::Legal` computes it
+    hash: Zobrist64,
+    // Hash of the pawns alone, keying the pawn structure evaluation
+    pawn_hash: Zobrist64,
+    // Pieces giving check to the side to move
+    checkers: Bitboard,
+    // Evaluation terms updated along with the pieces
+    accumulator: Accumulator,
+    history: Vec<Undo>,
+}
+
+impl From<&Chess> for Board {
+    fn from(position: &Chess) -> Self {
+        Board {
+            board: position.board().clone(),
+            turn: position.turn(),
+            castling_rights: position.castles().castling_rights(),
+            ep_square: position.ep_square(EnPassantMode::Legal),
+            halfmoves: position.halfmoves(),
+            fullmoves: position.fullmoves().get(),
+            hash: position.zobrist_hash(EnPassantMode::Legal),
+            pawn_hash: pawn_hash(position.board()),
+            checkers: position.checkers(),
+            accumulator: Accumulator::new(position.board()),
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::from(&Chess::default())
+    }
+}
+
+impl Board {
+    /// Placement of the pieces.
+    pub fn board(&self) -> &shakmaty::Board {
+        &self.board
+    }
+
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    pub fn castling_rights(&self) -> Bitboard {
+        self.castling_rights
+    }
+
+    /// Target square of a legal en passant capture, if there is one.
+    pub fn ep_square(&self) -> Option<Square> {
+        self.ep_square
+    }
+
+    /// Number of plies since the last capture or pawn move.
+    pub fn halfmoves(&self) -> u32 {
+        self.halfmoves
+    }
+
+    pub fn hash(&self) -> Zobrist64 {
+        self.hash
+    }
+
+    pub fn pawn_hash(&self) -> Zobrist64 {
+        self.pawn_hash
+    }
+
+    /// Pieces giving check to the side to move.
+    pub fn checkers(&self) -> Bitboard {
+        self.checkers
+    }
+
+    pub fn accumulator(&self) -> &Accumulator {
+        &self.accumulator
+    }
+
+    pub fn is_check(&self) -> bool {
+        self.checkers.any()
+    }
+
+    /// Tests whether the game has ended by checkmate, stalemate or insufficient material.
+    pub fn is_game_over(&self) -> bool {
+        self.legal_moves().is_empty() || self.is_insufficient_material()
+    }
+
+    pub fn is_checkmate(&self) -> bool {
+        self.is_check() && self.legal_moves().is_empty()
+    }
+
+    /// Tests whether the game is drawn by insufficient material or the fifty-move rule. Moves
+    /// are only generated when a check reaches the fifty-move limit, since mate takes precedence.
+    pub fn is_draw(&self) -> bool {
+        self.is_insufficient_material() || self.halfmoves >= 100 && !self.is_checkmate()
+    }
+
+    /// Tests whether neither side can possibly deliver mate.
+    pub fn is_insufficient_material(&self) -> bool {
+        self.has_insufficient_material(Color::White) && self.has_insufficient_material(Color::Black)
+    }
+
+    /// Tests whether the given side cannot possibly deliver mate, following the rules of
+    /// `Position::has_insufficient_material`.
+    pub fn has_insufficient_material(&self, color: Color) -> bool {
+        let board = &self.board;
+        let ours = board.by_color(color);
+
+        if (ours & (board.pawns() | board.rooks_and_queens())).any() {
+            return false;
+        }
+
+        // A lone knight cannot mate unless the opponent has pieces to block its own king
+        if (ours & board.knights()).any() {
+            return ours.count() <= 2
+                && (board.by_color(!color) & !board.kings() & !board.queens()).is_empty();
+        }
+
+        // Bishops cannot mate if they all stand on squares of one colour
+        if (ours & board.bishops()).any() {
+            let same_color = (board.bishops() & Bitboard::DARK_SQUARES).is_empty()
+                || (board.bishops() & Bitboard::LIGHT_SQUARES).is_empty();
+            return same_color && board.knights().is_empty() && board.pawns().is_empty();
+        }
+
+        true
+    }
+
+    /// Converts the position back to a shakmaty position.
+    pub fn to_chess(&self) -> Chess {
+        let setup = Setup {
+            board: self.board.clone(),
+            turn: self.turn,
+            castling_rights: self.castling_rights,
+            ep_square: self.ep_square,
+            halfmoves: self.halfmoves,
+            fullmoves: NonZeroU32::new(self.fullmoves).unwrap(),
+            ..Setup::empty()
+        };
+        Chess::from_setup(setup, CastlingMode::Standard).expect("Board holds a legal position")
+    }
+
+    /// Plays a legal move.
+    pub fn play(&mut self, mv: Move) {
+        self.save(Some(mv));
+        let us = self.turn;
+        let rights_before = self.castling_rights;
+
+        self.halfmoves = if mv.is_zeroing() {
+            0
+        } else {
+            self.halfmoves + 1
+        };
+
+        let mut ep_candidate = None;
+        match mv {
+            Move::Normal {
+                role,
+                from,
+                capture,
+                to,
+                promotion,
+            } => {
+                if let Some(captured) = capture {
+                    self.remove(to, captured.of(!us));
+                }
+                self.remove(from, role.of(us));
+                self.put(to, promotion.unwrap_or(role).of(us));
+
+                if role == Role::King {
+                    self.castling_rights &= !Bitboard::from_rank(us.backrank());
+                }
+                // A rook leaving or being captured on its square loses its right to castle
+                self.castling_rights &= !(Bitboard::from_square(from) | to);
+
+                if role == Role::Pawn && from.distance(to) == 2 {
+                    ep_candidate = Some(Square::from_coords(
+                        from.file(),
+                        us.fold_wb(Rank::Third, Rank::Sixth),
+                    ));
+                }
+            }
+            Move::EnPassant { from, to } => {
+                self.remove(
+                    Square::from_coords(to.file(), from.rank()),
+                    Role::Pawn.of(!us),
+                );
+                self.remove(from, Role::Pawn.of(us));
+                self.put(to, Role::Pawn.of(us));
+            }
+            Move::Castle { king, rook } => {
+                let (king_to, rook_to) = castling_targets(king, rook);
+                self.remove(king, Role::King.of(us));
+                self.remove(rook, Role::Rook.of(us));
+                self.put(rook_to, Role::Rook.of(us));
+                self.put(king_to, Role::King.of(us));
+                self.castling_rights &= !Bitboard::from_rank(us.backrank());
+            }
+            Move::Put { .. } => unreachable!("Drops are not part of standard chess"),
+        }
+
+        for rook in rights_before ^ self.castling_rights {
+            self.hash ^= castling_key(rook);
+        }
+
+        if us == Color::Black {
+            self.fullmoves += 1;
+        }
+        self.pass_turn();
+
+        // The en passant square only counts if the capture is actually legal
+        self.ep_square = ep_candidate.filter(|&square| self.has_legal_en_passant(square));
+        if let Some(square) = self.ep_square {
+            self.hash ^= Zobrist64::zobrist_for_en_passant_file(square.file());
+        }
+    }
+
+    /// Passes the turn to the opponent, which must not be in check.
+    pub fn play_null(&mut self) {
+        self.save(None);
+        self.halfmoves += 1;
+        self.pass_turn();
+    }
+
+    /// Takes back the last move or null move.
+    pub fn undo(&mut self) {
+        let undo = self.history.pop().expect("No move to undo");
+        self.turn = !self.turn;
+        let us = self.turn;
+
+        match undo.mv {
+            Some(Move::Normal {
+                role,
+                from,
+                capture,
+                to,
+                ..
+            }) => {
+                self.board.discard_piece_at(to);
+                self.board.set_piece_at(from, role.of(us));
+                if let Some(captured) = capture {
+                    self.board.set_piece_at(to, captured.of(!us));
+                }
+            }
+            Some(Move::EnPassant { from, to }) => {
+                self.board.discard_piece_at(to);
+                self.board.set_piece_at(from, Role::Pawn.of(us));
+                self.board.set_piece_at(
+                    Square::from_coords(to.file(), from.rank()),
+                    Role::Pawn.of(!us),
+                );
+            }
+            Some(Move::Castle { king, rook }) => {
+                let (king_to, rook_to) = castling_targets(king, rook);
+                self.board.discard_piece_at(king_to);
+                self.board.discard_piece_at(rook_to);
+                self.board.set_piece_at(king, Role::King.of(us));
+                self.board.set_piece_at(rook, Role::Rook.of(us));
+            }
+            Some(Move::Put { .. }) | None => {}
+        }
+
+        self.castling_rights = undo.castling_rights;
+        self.ep_square = undo.ep_square;
+        self.halfmoves = undo.halfmoves;
+        self.fullmoves = undo.fullmoves;
+        self.hash = undo.hash;
+        self.pawn_hash = undo.pawn_hash;
+        self.checkers = undo.checkers;
+        self.accumulator = undo.accumulator;
+    }
+
+    /// Pushes the state needed to undo a move onto the history.
+    fn save(&mut self, mv: Option<Move>) {
+        self.history.push(Undo {
+            mv,
+            castling_rights: self.castling_rights,
+            ep_square: self.ep_square,
+            halfmoves: self.halfmoves,
+            fullmoves: self.fullmoves,
+            hash: self.hash,
+            pawn_hash: self.pawn_hash,
+            checkers: self.checkers,
+            accumulator: self.accumulator,
+        });
+
+        // The en passant right expires with any move
+        if let Some(square) = self.ep_square.take() {
+            self.hash ^= Zobrist64::zobrist_for_en_passant_file(square.file());
+        }
+    }
+
+    /// Gives the turn to the opponent and finds the pieces checking them.
+    fn pass_turn(&mut self) {
+        self.turn = !self.turn;
+        self.hash ^= Zobrist64::zobrist_for_white_turn();
+        self.checkers = self.attackers(self.king(), !self.turn, self.board.occupied());
+    }
+
+    fn remove(&mut self, square: Square, piece: Piece) {
+        self.board.discard_piece_at(square);
+        self.hash ^= Zobrist64::zobrist_for_piece(square, piece);
+        if piece.role == Role::Pawn {
+            self.pawn_hash ^= Zobrist64::zobrist_for_piece(square, piece);
+        }
+        self.accumulator.remove(square, piece);
+    }
+
+    fn put(&mut self, square: Square, piece: Piece) {
+        self.board.set_piece_at(square, piece);
+        self.hash ^= Zobrist64::zobrist_for_piece(square, piece);
+        if piece.role == Role::Pawn {
+            self.pawn_hash ^= Zobrist64::zobrist_for_piece(square, piece);
+        }
+        self.accumulator.put(square, piece);
+    }
+
+    /// Square of the king of the side to move.
+    fn king(&self) -> Square {
+        self.board
+            .king_of(self.turn)
+            .expect("Both sides have a king")
+    }
+
+    /// Pieces of the given side attacking a square, with sliders seeing through the squares
+    /// missing from `occupied`.
+    fn attackers(&self, square: Square, attacker: Color, occupied: Bitboard) -> Bitboard {
+        self.board.by_color(attacker)
+            & ((attacks::rook_attacks(square, occupied) & self.board.rooks_and_queens())
+                | (attacks::bishop_attacks(square, occupied) & self.board.bishops_and_queens())
+                | (attacks::knight_attacks(square) & self.board.knights())
+                | (attacks::king_attacks(square) & self.board.kings())
+                | (attacks::pawn_attacks(!attacker, square) & self.board.pawns()))
+    }
+}
+
+/// Hashes the pawns of a board alone.
+fn pawn_hash(board: &shakmaty::Board) -> Zobrist64 {
+    let mut hash = Zobrist64(0);
+    for color in Color::ALL {
+        let pawn = Role::Pawn.of(color);
+        for square in board.by_piece(pawn) {
+            hash ^= Zobrist64::zobrist_for_piece(square, pawn);
+        }
+    }
+    hash
+}
+
+/// Destinations of the king and rook when castling in standard chess.
+fn castling_targets(king: Square, rook: Square) -> (Square, Square) {
+    let side = CastlingSide::from_queen_side(rook < king);
+    (
+        Square::from_coords(side.king_to_file(), king.rank()),
+        Square::from_coords(side.rook_to_file(), king.rank()),
+    )
+}
+
+/// Zobrist key of the castling right belonging to a rook in its corner.
+fn castling_key(rook: Square) -> Zobrist64 {
+    let color = Color::from_white(rook.rank() == Rank::First);
+    let side = CastlingSide::from_queen_side(rook.file() == File::A);
+    Zobrist64::zobrist_for_castling_right(color, side)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, uci::UciMove};
+    use std::str::FromStr;
+
+    fn parse_position(fen_str: &str) -> Chess {
+        Fen::from_str(fen_str)
+            .unwrap()
+            .into_position(CastlingMode::Standard)
+            .unwrap()
+    }
+
+    /// Plays moves given in UCI notation on both representations and compares them.
+    fn play_and_compare(fen_str: &str, moves: &[&str]) {
+        let mut pos = parse_position(fen_str);
+        let mut board = Board::from(&pos);
+
+        for uci in moves {
+            let mv = UciMove::from_str(uci).unwrap().to_move(&pos).unwrap();
+            pos.play_unchecked(mv);
+            board.play(mv);
+            assert_eq!(board.hash(), pos.zobrist_hash(EnPassantMode::Legal));
+            assert_eq!(board.pawn_hash(), Board::from(&pos).pawn_hash());
+            assert_eq!(board.to_chess(), pos);
+        }
+
+        for _ in moves {
+            board.undo();
+        }
+        assert_eq!(board.to_chess(), parse_position(fen_str));
+        assert_eq!(board.hash(), Board::from(&parse_position(fen_str)).hash());
+    }
+
+    #[test]
+    fn test_noisy_and_quiet_moves_partition_legal_moves() {
+        for fen_str in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+            "4k3/8/8/8/8/8/3q4/R3K2R w KQ - 0 1",
+        ] {
+            let board = Board::from(&parse_position(fen_str));
+            let noisy = board.noisy_moves();
+            let quiet = board.quiet_moves();
+            assert!(noisy.iter().all(|mv| mv.is_capture() || mv.is_promotion()));
+            assert!(quiet
+                .iter()
+                .all(|mv| !mv.is_capture() && !mv.is_promotion()));
+
+            let key = |mv: &Move| mv.to_string();
+            let mut split: Vec<Move> = noisy.into_iter().chain(quiet).collect();
+            let mut legal = board.legal_moves().to_vec();
+            split.sort_by_key(key);
+            legal.sort_by_key(key);
+            assert_eq!(split, legal, "{fen_str}");
+        }
+    }
+
+    #[test]
+    fn test_castling_and_rights() {
+        play_and_compare(
+            "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1",
+            &["e1g1", "h8g8", "h2h3", "e8c8", "g1h1"],
+        );
+    }
+
+    #[test]
+    fn test_en_passant_and_promotion() {
+        play_and_compare(
+            "4k3/1P6/8/8/5p2/8/4P3/4K3 w - - 0 1",
+            &["e2e4", "f4e3", "b7b8q", "e8d7", "b8b1"],
+        );
+    }
+
+    #[test]
+    fn test_pinned_en_passant_is_not_hashed() {
+        // The capture on e3 would expose the black king on the fourth rank
+        let mut board = Board::from(&parse_position("8/8/8/8/k4p1R/8/4P3/4K3 w - - 0 1"));
+        let e4 = Move::Normal {
+            role: Role::Pawn,
+            from: Square::E2,
+            capture: None,
+            to: Square::E4,
+            promotion: None,
+        };
+        board.play(e4);
+        assert_eq!(board.ep_square(), None);
+        assert_eq!(
+            board.hash(),
+            board.to_chess().zobrist_hash(EnPassantMode::Legal)
+        );
+    }
+
+    #[test]
+    fn test_draw_by_rule() {
+        // Bare kings, and a quiet position at the fifty-move limit
+        assert!(Board::from(&parse_position("8/8/4k3/8/8/3K4/8/8 w - - 0 1")).is_draw());
+        assert!(Board::from(&parse_position("8/8/4k3/8/8/3K4/7R/8 w - - 100 80")).is_draw());
+        assert!(!Board::from(&parse_position("8/8/4k3/8/8/3K4/7R/8 w - - 99 80")).is_draw());
+
+        // Mate on the move reaching the limit still counts
+        assert!(!Board::from(&parse_position("R5k1/5ppp/8/8/8/8/8/6K1 b - - 100 80")).is_draw());
+    }
+
+    #[test]
+    fn test_null_move() {
+        let pos = parse_position("4k3/8/8/8/3p4/8/2P5/4K3 w - - 0 1");
+        let mut board = Board::from(&pos);
+        board.play(UciMove::from_str("c2c4").unwrap().to_move(&pos).unwrap());
+        let before = board.hash();
+        assert!(board.ep_square().is_some());
+
+        board.play_null();
+        assert_eq!(
+            board.hash(),
+            board.to_chess().zobrist_hash(EnPassantMode::Legal)
+        );
+        board.undo();
+        assert_eq!(board.hash(), before);
+        assert!(board.ep_square().is_some());
+    }
+}
@@ -0,0 +1,388 @@
+use crate::board::{castling_targets, Board};
+use shakmaty::{attacks, Bitboard, CastlingSide, Move, MoveList, Rank, Role, Square};
+
+/// Pieces a pawn can promote to, in the order they are generated.
+const PROMOTIONS: [Role; 4] = [Role::Queen, Role::Rook, Role::Bishop, Role::Knight];
+
+impl Board {
+    /// Generates all legal moves.
+    pub fn legal_moves(&self) -> MoveList {
+        self.generate(!self.us())
+    }
+
+    /// Generates all legal captures, including en passant.
+    pub fn capture_moves(&self) -> MoveList {
+        self.generate(self.them())
+    }
+
+    /// Generates all legal captures and promotions.
+    pub fn noisy_moves(&self) -> MoveList {
+        let promotion_squares = Bitboard::from_rank((!self.turn).backrank()) & !self.us();
+        let mut moves = self.generate(self.them() | promotion_squares);
+        moves.retain(|mv| mv.is_capture() || mv.is_promotion());
+        moves
+    }
+
+    /// Generates all legal moves that neither capture nor promote, including castling.
+    pub fn quiet_moves(&self) -> MoveList {
+        let mut moves = self.generate(!self.board.occupied());
+        moves.retain(|mv| !mv.is_promotion());
+        moves
+    }
+
+    /// Generates the legal moves to squares in `target`, along with en passant captures if any
+    /// enemy piece is a target and castling if every empty square is. Moves are generated for
+    /// every piece as if it was not pinned, and the moves of pinned pieces leaving the line to
+    /// their king are dropped afterwards.
+    fn generate(&self, target: Bitboard) -> MoveList {
+        let mut moves = MoveList::new();
+        let king = self.king();
+
+        if self.checkers.is_empty() {
+            self.gen_non_king(target, &mut moves);
+            self.gen_king(king, target, &mut moves);
+            if (!self.board.occupied() & !target).is_empty() {
+                self.gen_castling(king, &mut moves);
+            }
+        } else {
+            self.gen_evasions(king, target, &mut moves);
+        }
+        if (target & self.them()).any() {
+            self.gen_en_passant(king, &mut moves);
+        }
+
+        let blockers = self.slider_blockers(king);
+        if blockers.any() {
+            moves.retain(|mv| match *mv {
+                Move::Normal { from, to, .. } => {
+                    !blockers.contains(from) || attacks::aligned(from, to, king)
+                }
+                _ => true,
+            });
+        }
+
+        moves
+    }
+
+    /// Tests whether a move is legal without generating all moves.
+    pub fn is_legal(&self, mv: Move) -> bool {
+        let king = self.king();
+
+        match mv {
+            Move::Normal {
+                role,
+                from,
+                capture,
+                to,
+                promotion,
+            } => {
+                if self.board.piece_at(from) != Some(role.of(self.turn))
+                    || self.board.role_at(to) != capture
+                    || self.us().contains(to)
+                {
+                    return false;
+                }
+
+                // Pawns promote exactly when reaching the last rank, and only to pieces
+                let last_rank = to.rank() == (!self.turn).backrank();
+                let promotes_correctly = match promotion {
+                    Some(promoted) => {
+                        role == Role::Pawn && last_rank && PROMOTIONS.contains(&promoted)
+                    }
+                    None => role != Role::Pawn || !last_rank,
+                };
+                if !promotes_correctly {
+                    return false;
+                }
+
+                let reachable = if role == Role::Pawn {
+                    self.pawn_targets(from, capture.is_some()).contains(to)
+                } else {
+                    attacks::attacks(from, role.of(self.turn), self.board.occupied()).contains(to)
+                };
+                if !reachable {
+                    return false;
+                }
+
+                if role == Role::King {
+                    return self.is_safe_for_king(king, to);
+                }
+
+                // Only blocking the check or capturing the checker help against a single check
+                if let Some(checker) = self.checkers.first() {
+                    if self.checkers.more_than_one()
+                        || !attacks::between(king, checker).with(checker).contains(to)
+                    {
+                        return false;
+                    }
+                }
+
+                !self.slider_blockers(king).contains(from) || attacks::aligned(from, to, king)
+            }
+            Move::EnPassant { .. } => {
+                let mut moves = MoveList::new();
+                self.gen_en_passant(king, &mut moves);
+                moves.contains(&mv)
+            }
+            Move::Castle { .. } => {
+                let mut moves = MoveList::new();
+                if self.checkers.is_empty() {
+                    self.gen_castling(king, &mut moves);
+                }
+                moves.contains(&mv)
+            }
+            Move::Put { .. } => false,
+        }
+    }
+
+    /// Tests whether a legal move gives check, without playing it.
+    pub fn gives_check(&self, mv: Move) -> bool {
+        let king = self
+            .board
+            .king_of(!self.turn)
+            .expect("Both sides have a king");
+
+        let mut occupied = self.board.occupied();
+        let mut rooks = self.our(Role::Rook) | self.our(Role::Queen);
+        let mut bishops = self.our(Role::Bishop) | self.our(Role::Queen);
+        let mut knights = self.our(Role::Knight);
+        let mut pawns = self.our(Role::Pawn);
+
+        // The piece that could give check directly, and where it lands
+        let (from, to, role) = match mv {
+            Move::Normal {
+                role,
+                from,
+                to,
+                promotion,
+                ..
+            } => (from, to, promotion.unwrap_or(role)),
+            Move::EnPassant { from, to } => {
+                occupied.discard(Square::from_coords(to.file(), from.rank()));
+                (from, to, Role::Pawn)
+            }
+            Move::Castle { king, rook } => {
+                let (king_to, rook_to) = castling_targets(king, rook);
+                occupied = occupied.without(king).with(king_to);
+                (rook, rook_to, Role::Rook)
+            }
+            Move::Put { .. } => return false,
+        };
+
+        occupied = occupied.without(from).with(to);
+        for pieces in [&mut rooks, &mut bishops, &mut knights, &mut pawns] {
+            pieces.discard(from);
+        }
+        match role {
+            Role::Pawn => pawns.add(to),
+            Role::Knight => knights.add(to),
+            Role::Bishop => bishops.add(to),
+            Role::Rook => rooks.add(to),
+            Role::Queen => {
+                rooks.add(to);
+                bishops.add(to);
+            }
+            Role::King => {}
+        }
+
+        // Checks are given directly by the moved piece or discovered by sliders behind it
+        (attacks::rook_attacks(king, occupied) & rooks).any()
+            || (attacks::bishop_attacks(king, occupied) & bishops).any()
+            || (attacks::knight_attacks(king) & knights).any()
+            || (attacks::pawn_attacks(!self.turn, king) & pawns).any()
+    }
+
+    /// Tests whether a pawn of the side to move can legally capture en passant on a square.
+    pub(crate) fn has_legal_en_passant(&self, square: Square) -> bool {
+        let capturers = attacks::pawn_attacks(!self.turn, square) & self.our(Role::Pawn);
+        capturers
+            .into_iter()
+            .any(|from| self.is_safe_en_passant(self.king(), from, square))
+    }
+
+    fn us(&self) -> Bitboard {
+        self.board.by_color(self.turn)
+    }
+
+    fn them(&self) -> Bitboard {
+        self.board.by_color(!self.turn)
+    }
+
+    fn our(&self, role: Role) -> Bitboard {
+        self.board.by_piece(role.of(self.turn))
+    }
+
+    /// Generates moves of all pieces except the king to squares in `target`.
+    fn gen_non_king(&self, target: Bitboard, moves: &mut MoveList) {
+        self.gen_pawn_moves(target, moves);
+
+        let occupied = self.board.occupied();
+        for role in [Role::Knight, Role::Bishop, Role::Rook, Role::Queen] {
+            for from in self.our(role) {
+                for to in attacks::attacks(from, role.of(self.turn), occupied) & target {
+                    moves.push(Move::Normal {
+                        role,
+                        from,
+                        capture: self.board.role_at(to),
+                        to,
+                        promotion: None,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Generates pawn pushes and captures to squares in `target`, except en passant.
+    fn gen_pawn_moves(&self, target: Bitboard, moves: &mut MoveList) {
+        let last_rank = (!self.turn).backrank();
+
+        for from in self.our(Role::Pawn) {
+            for to in self.pawn_targets(from, true) | self.pawn_targets(from, false) {
+                if !target.contains(to) {
+                    continue;
+                }
+
+                let capture = self.board.role_at(to);
+                if to.rank() == last_rank {
+                    for promoted in PROMOTIONS {
+                        moves.push(Move::Normal {
+                            role: Role::Pawn,
+                            from,
+                            capture,
+                            to,
+                            promotion: Some(promoted),
+                        });
+                    }
+                } else {
+                    moves.push(Move::Normal {
+                        role: Role::Pawn,
+                        from,
+                        capture,
+                        to,
+                        promotion: None,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Squares a pawn can capture on, or push to, ignoring en passant and pins.
+    fn pawn_targets(&self, from: Square, captures: bool) -> Bitboard {
+        if captures {
+            return attacks::pawn_attacks(self.turn, from) & self.them();
+        }
+
+        let forward = self.turn.fold_wb(8, -8);
+        let occupied = self.board.occupied();
+        let mut targets = Bitboard::EMPTY;
+        if let Some(single) = from.offset(forward).filter(|&to| !occupied.contains(to)) {
+            targets.add(single);
+
+            // Pawns on their starting rank may advance two squares
+            if from.rank() == self.turn.relative_rank(Rank::Second) {
+                if let Some(double) = single.offset(forward).filter(|&to| !occupied.contains(to)) {
+                    targets.add(double);
+                }
+            }
+        }
+        targets
+    }
+
+    /// Generates king moves to squares in `target` that are not attacked.
+    fn gen_king(&self, king: Square, target: Bitboard, moves: &mut MoveList) {
+        for to in attacks::king_attacks(king) & target {
+            if self.is_safe_for_king(king, to) {
+                moves.push(Move::Normal {
+                    role: Role::King,
+                    from: king,
+                    capture: self.board.role_at(to),
+                    to,
+                    promotion: None,
+                });
+            }
+        }
+    }
+
+    /// Generates castling moves, assuming the king is not in check.
+    fn gen_castling(&self, king: Square, moves: &mut MoveList) {
+        let backrank = self.turn.backrank();
+        for rook in self.castling_rights & Bitboard::from_rank(backrank) & self.our(Role::Rook) {
+            let side = CastlingSide::from_queen_side(rook < king);
+            let king_to = Square::from_coords(side.king_to_file(), backrank);
+
+            // Every square between king and rook is empty, and the king passes no attacked square
+            let path = attacks::between(king, rook);
+            let king_path = attacks::between(king, king_to).with(king_to);
+            if (path & self.board.occupied()).any()
+                || king_path
+                    .into_iter()
+                    .any(|square| !self.is_safe_for_king(king, square))
+            {
+                continue;
+            }
+
+            moves.push(Move::Castle { king, rook });
+        }
+    }
+
+    /// Generates the moves to squares in `target` escaping check, except en passant.
+    fn gen_evasions(&self, king: Square, target: Bitboard, moves: &mut MoveList) {
+        self.gen_king(king, target, moves);
+
+        // Against a single check, other pieces can capture the checker or block its line
+        if let Some(checker) = self.checkers.single_square() {
+            let blocks = attacks::between(king, checker).with(checker);
+            self.gen_non_king(target & blocks, moves);
+        }
+    }
+
+    /// Generates legal en passant captures.
+    fn gen_en_passant(&self, king: Square, moves: &mut MoveList) {
+        let Some(to) = self.ep_square else {
+            return;
+        };
+
+        for from in attacks::pawn_attacks(!self.turn, to) & self.our(Role::Pawn) {
+            if self.is_safe_en_passant(king, from, to) {
+                moves.push(Move::EnPassant { from, to });
+            }
+        }
+    }
+
+    /// Tests whether the king is safe after capturing en passant, which removes two pawns from
+    /// the board at once.
+    fn is_safe_en_passant(&self, king: Square, from: Square, to: Square) -> bool {
+        let captured = Square::from_coords(to.file(), from.rank());
+        let occupied = self
+            .board
+            .occupied()
+            .without(from)
+            .without(captured)
+            .with(to);
+        (self.attackers(king, !self.turn, occupied) & !Bitboard::from_square(captured)).is_empty()
+    }
+
+    /// Tests whether the king can stand on a square without being attacked, seeing through the
+    /// square it leaves.
+    fn is_safe_for_king(&self, king: Square, square: Square) -> bool {
+        let occupied = self.board.occupied().without(king);
+        self.attackers(square, !self.turn, occupied).is_empty()
+    }
+
+    /// Our pieces that are the only piece between an enemy slider and our king.
+    fn slider_blockers(&self, king: Square) -> Bitboard {
+        let snipers = self.them()
+            & ((attacks::rook_attacks(king, Bitboard::EMPTY) & self.board.rooks_and_queens())
+                | (attacks::bishop_attacks(king, Bitboard::EMPTY)
+                    & self.board.bishops_and_queens()));
+
+        let mut blockers = Bitboard::EMPTY;
+        for sniper in snipers {
+            let between = attacks::between(king, sniper) & self.board.occupied();
+            if !between.more_than_one() {
+                blockers |= between;
+            }
+        }
+        blockers & self.us()
+    }
+}
@@ -1,16 +1,27 @@
-use crate::{SearchCommand, SearchControl, SearchInfo};
+use crate::{
+    eval::evaluator::EvaluatorKind,
+    search::{transposition::Bound, MATE_THRESHOLD},
+    SearchCommand, SearchControl, SearchInfo,
+};
 use chrono::Local;
 use crossbeam_channel::{select, Receiver, Sender};
 use shakmaty::{CastlingMode, Chess, Position};
-use shakmaty_uci::{UciInfo, UciInfoScore, UciMessage, UciMove, UciSearchControl, UciTimeControl};
+use shakmaty_uci::{
+    UciInfo, UciInfoScore, UciMessage, UciMove, UciOptionConfig, UciSearchControl, UciTimeControl,
+};
 use std::{fs::OpenOptions, io::Write};
 
+/// Name of the UCI option choosing the evaluator.
+const EVALUATOR_OPTION: &str = "Evaluator";
+
 /// Handles incoming commands, sends outgoing messages and produces runtime logs.
 pub struct Controller {
     input_rx: Receiver<UciMessage>,
     cmd_tx: Sender<SearchCommand>,
     info_rx: Receiver<SearchInfo>,
     position: Chess,
+    // Evaluator the search thread is currently using
+    evaluator: EvaluatorKind,
     log_file: &'static str,
 }
 
@@ -20,12 +31,30 @@ impl Controller {
         cmd_tx: Sender<SearchCommand>,
         info_rx: Receiver<SearchInfo>,
         log_file: &'static str,
+    ) -> Self {
+        Self::with_evaluator(
+            input_rx,
+            cmd_tx,
+            info_rx,
+            log_file,
+            EvaluatorKind::default(),
+        )
+    }
+
+    /// Creates a controller for a search thread started with the given evaluator.
+    pub fn with_evaluator(
+        input_rx: Receiver<UciMessage>,
+        cmd_tx: Sender<SearchCommand>,
+        info_rx: Receiver<SearchInfo>,
+        log_file: &'static str,
+        evaluator: EvaluatorKind,
     ) -> Self {
         let controller = Controller {
             input_rx,
             cmd_tx,
             info_rx,
             position: Chess::default(),
+            evaluator,
             log_file,
         };
 
@@ -72,10 +101,31 @@ impl Controller {
                     name: None,
                     author: Some(env!("CARGO_PKG_AUTHORS").into()),
                 });
+                self.send(UciMessage::Option(UciOptionConfig::Combo {
+                    name: EVALUATOR_OPTION.into(),
+                    default: Some(self.evaluator.name().into()),
+                    var: EvaluatorKind::ALL
+                        .into_iter()
+                        .map(|kind| kind.name().into())
+                        .collect(),
+                }));
                 self.send(UciMessage::UciOk);
             }
             UciMessage::IsReady => self.send(UciMessage::ReadyOk),
 
+            // Switch the evaluator used by the search
+            UciMessage::SetOption { name, value }
+                if name.eq_ignore_ascii_case(EVALUATOR_OPTION) =>
+            {
+                match value.unwrap_or_default().parse::<EvaluatorKind>() {
+                    Ok(kind) => {
+                        self.evaluator = kind;
+                        self.cmd_tx.send(SearchCommand::SetEvaluator(kind)).unwrap();
+                    }
+                    Err(error) => self.log(&format!("ERR: {error}")),
+                }
+            }
+
             // Reset
             UciMessage::UciNewGame => {
                 self.position = Chess::default();
@@ -96,6 +146,21 @@ impl Controller {
                 self.position = position;
             }
 
+            // Prove a mate in a given number of moves
+            UciMessage::Go {
+                search_control:
+                    Some(UciSearchControl {
+                        mate: Some(moves), ..
+                    }),
+                ..
+            } => self
+                .cmd_tx
+                .send(SearchCommand::Start {
+                    position: self.position.clone(),
+                    control: SearchControl::Mate(moves),
+                })
+                .unwrap(),
+
             // Search to fixed depth
             UciMessage::Go {
                 search_control:
@@ -151,18 +216,29 @@ impl Controller {
                 ponder: None,
             }),
 
+            // Emit message to user interface
+            SearchInfo::String(message) => self.send(UciMessage::Info(UciInfo {
+                string: Some(message),
+                ..Default::default()
+            })),
+
             // Emit info to user interface
             SearchInfo::Info {
                 depth,
+                seldepth,
                 pv,
                 score,
                 nodes,
+                bound,
             } => {
                 let info_msg = UciMessage::Info(UciInfo {
                     depth: Some(depth),
+                    sel_depth: Some(seldepth),
                     score: Some(UciInfoScore {
-                        cp: Some(score),
-                        ..Default::default()
+                        cp: (score.abs() < MATE_THRESHOLD).then_some(score),
+                        mate: mate_in(score),
+                        lower_bound: (bound == Bound::Lower).then_some(true),
+                        upper_bound: (bound == Bound::Upper).then_some(true),
                     }),
                     pv: pv
                         .into_iter()
@@ -188,6 +264,17 @@ impl Controller {
     }
 }
 
+/// Converts a mate score to moves until mate, negative when the side to move is mated.
+fn mate_in(score: i32) -> Option<i8> {
+    if score >= MATE_THRESHOLD {
+        Some(((i32::MAX - score + 1) / 2) as i8)
+    } else if score <= -MATE_THRESHOLD {
+        Some((-(score + i32::MAX + 1) / 2) as i8)
+    } else {
+        None
+    }
+}
+
 impl Drop for Controller {
     fn drop(&mut self) {
         self.log("------ Engine closed ------");
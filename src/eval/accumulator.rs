@@ -0,0 +1,72 @@
+use crate::eval::{
+    material::material_score,
+    phase::{phase_weight, Phase},
+    pst::position_score,
+};
+use shakmaty::{Board, Piece, Square};
+
+/// Middlegame and endgame scores of the material and piece placement from White's point of view,
+/// along with the phase, kept up to date as pieces are put on and removed from the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Accumulator {
+    pub middlegame: i32,
+    pub endgame: i32,
+    pub phase: Phase,
+}
+
+impl Accumulator {
+    /// Computes the scores of a board from scratch.
+    pub fn new(board: &Board) -> Accumulator {
+        let mut accumulator = Accumulator::default();
+        for (square, piece) in board.iter() {
+            accumulator.put(square, piece);
+        }
+        accumulator
+    }
+
+    pub fn put(&mut self, square: Square, piece: Piece) {
+        let (middlegame, endgame) = piece_scores(square, piece);
+        self.middlegame += middlegame;
+        self.endgame += endgame;
+        self.phase.0 += phase_weight(piece.role);
+    }
+
+    pub fn remove(&mut self, square: Square, piece: Piece) {
+        let (middlegame, endgame) = piece_scores(square, piece);
+        self.middlegame -= middlegame;
+        self.endgame -= endgame;
+        self.phase.0 -= phase_weight(piece.role);
+    }
+}
+
+/// Scores of a single piece from White's point of view.
+fn piece_scores(square: Square, piece: Piece) -> (i32, i32) {
+    let (middlegame, endgame) = position_score(piece, square);
+    let material = material_score(piece.role);
+    let sign = piece.color.fold_wb(1, -1);
+    (sign * (material + middlegame), sign * (material + endgame))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{Chess, Color, Position, Role};
+
+    #[test]
+    fn test_start_position_is_balanced() {
+        let accumulator = Accumulator::new(Chess::default().board());
+        assert_eq!((accumulator.middlegame, accumulator.endgame), (0, 0));
+        assert_eq!(accumulator.phase, Phase::new(Chess::default().board()));
+    }
+
+    #[test]
+    fn test_put_and_remove_cancel() {
+        let mut accumulator = Accumulator::new(Chess::default().board());
+        let before = accumulator;
+        let knight = Role::Knight.of(Color::White);
+        accumulator.put(Square::E4, knight);
+        assert!(accumulator.middlegame > before.middlegame);
+        accumulator.remove(Square::E4, knight);
+        assert_eq!(accumulator, before);
+    }
+}
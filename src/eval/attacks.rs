@@ -0,0 +1,62 @@
+use shakmaty::{attacks, Bitboard, Board, Color, Role};
+
+/// Squares attacked by the pieces of both sides, built once per evaluation and shared by the
+/// terms looking at the control of the board.
+#[derive(Clone, Debug, Default)]
+pub struct AttackMap {
+    // Attacks by color and role
+    by_role: [[Bitboard; 6]; 2],
+    // Attacks of all pieces by color
+    all: [Bitboard; 2],
+    // Squares attacked at least twice by color
+    double: [Bitboard; 2],
+}
+
+impl AttackMap {
+    pub fn new(board: &Board) -> AttackMap {
+        let mut map = AttackMap::default();
+        for color in Color::ALL {
+            for role in Role::ALL {
+                let piece = role.of(color);
+                for square in board.by_piece(piece) {
+                    let attacked = attacks::attacks(square, piece, board.occupied());
+                    map.by_role[color as usize][role as usize - 1] |= attacked;
+                    map.double[color as usize] |= map.all[color as usize] & attacked;
+                    map.all[color as usize] |= attacked;
+                }
+            }
+        }
+        map
+    }
+
+    pub fn by_role(&self, color: Color, role: Role) -> Bitboard {
+        self.by_role[color as usize][role as usize - 1]
+    }
+
+    pub fn all(&self, color: Color) -> Bitboard {
+        self.all[color as usize]
+    }
+
+    pub fn double(&self, color: Color) -> Bitboard {
+        self.double[color as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{Chess, Position, Square};
+
+    #[test]
+    fn test_start_position() {
+        let map = AttackMap::new(Chess::default().board());
+        let third_rank = Bitboard::from_rank(shakmaty::Rank::Third);
+        assert_eq!(map.by_role(Color::White, Role::Pawn), third_rank);
+        assert!(map.all(Color::White).contains(Square::F3));
+        // Guarded by the e2 and g2 pawns and the g1 knight, while b2 is guarded by the c1 bishop
+        assert!(map.double(Color::White).contains(Square::F3));
+        assert!(map.all(Color::White).contains(Square::B2));
+        assert!(!map.double(Color::White).contains(Square::B2));
+        assert!((map.all(Color::Black) & third_rank).is_empty());
+    }
+}
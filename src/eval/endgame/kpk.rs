@@ -0,0 +1,175 @@
+use shakmaty::{attacks, Color, File, Rank, Square};
+use std::sync::OnceLock;
+
+/// Positions by side to move, pawn on files A-D and ranks 2-7, and both king squares.
+const SIZE: usize = 2 * 24 * 64 * 64;
+
+// Results are flags, so the results of all successors of a position can be combined
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+/// Win or draw for every position of king and pawn against king, generated on first use.
+static BITBASE: OnceLock<Vec<bool>> = OnceLock::new();
+
+/// Tests whether White wins with king and pawn against the black king. The pawn must be on files
+/// A to D, which the other files mirror.
+pub fn probe(white_to_move: bool, white_king: Square, black_king: Square, pawn: Square) -> bool {
+    debug_assert!(pawn.file() <= File::D);
+    BITBASE.get_or_init(generate)[index(white_to_move, white_king, black_king, pawn)]
+}
+
+fn index(white_to_move: bool, white_king: Square, black_king: Square, pawn: Square) -> usize {
+    let pawn_index = pawn.file().to_usize() * 6 + pawn.rank().to_usize() - 1;
+    ((pawn_index * 64 + white_king.to_usize()) * 64 + black_king.to_usize()) * 2
+        + white_to_move as usize
+}
+
+/// Classifies all positions by retrograde analysis: positions decided by the rules are
+/// classified first, and the others are resolved from their successors until nothing changes.
+/// Positions left unknown are draws, since White never forces promotion from them.
+fn generate() -> Vec<bool> {
+    let mut results = vec![INVALID; SIZE];
+    let positions = || {
+        (0..4).flat_map(|file| {
+            (1..7).flat_map(move |rank| {
+                let pawn = Square::from_coords(File::new(file), Rank::new(rank));
+                Square::ALL.into_iter().flat_map(move |white_king| {
+                    Square::ALL.into_iter().flat_map(move |black_king| {
+                        [false, true]
+                            .map(|white_to_move| (white_to_move, white_king, black_king, pawn))
+                    })
+                })
+            })
+        })
+    };
+
+    for (white_to_move, white_king, black_king, pawn) in positions() {
+        results[index(white_to_move, white_king, black_king, pawn)] =
+            initial(white_to_move, white_king, black_king, pawn);
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (white_to_move, white_king, black_king, pawn) in positions() {
+            let i = index(white_to_move, white_king, black_king, pawn);
+            if results[i] == UNKNOWN {
+                results[i] = classify(&results, white_to_move, white_king, black_king, pawn);
+                changed |= results[i] != UNKNOWN;
+            }
+        }
+    }
+
+    results.into_iter().map(|result| result == WIN).collect()
+}
+
+/// Classifies a position by the rules alone, if possible.
+fn initial(white_to_move: bool, white_king: Square, black_king: Square, pawn: Square) -> u8 {
+    let push = pawn.offset(8).expect("Pawns stand below the last rank");
+
+    if white_king.distance(black_king) <= 1
+        || white_king == pawn
+        || black_king == pawn
+        || white_to_move && attacks::pawn_attacks(Color::White, pawn).contains(black_king)
+    {
+        INVALID
+    } else if white_to_move
+        && pawn.rank() == Rank::Seventh
+        && white_king != push
+        && (black_king.distance(push) > 1 || white_king.distance(push) == 1)
+    {
+        // The pawn promotes without being captured
+        WIN
+    } else if !white_to_move
+        && ((attacks::king_attacks(black_king)
+            & !(attacks::king_attacks(white_king) | attacks::pawn_attacks(Color::White, pawn)))
+        .is_empty()
+            || (attacks::king_attacks(black_king) & !attacks::king_attacks(white_king))
+                .contains(pawn))
+    {
+        // Stalemate, or the pawn is captured
+        DRAW
+    } else {
+        UNKNOWN
+    }
+}
+
+/// Classifies a position from the results of its successors.
+fn classify(
+    results: &[u8],
+    white_to_move: bool,
+    white_king: Square,
+    black_king: Square,
+    pawn: Square,
+) -> u8 {
+    let (good, bad) = if white_to_move {
+        (WIN, DRAW)
+    } else {
+        (DRAW, WIN)
+    };
+
+    // Illegal successors are invalid, which adds nothing
+    let mut successors = INVALID;
+    if white_to_move {
+        for to in attacks::king_attacks(white_king) {
+            successors |= results[index(false, to, black_king, pawn)];
+        }
+
+        let push = pawn.offset(8).expect("Pawns stand below the last rank");
+        if pawn.rank() < Rank::Seventh {
+            successors |= results[index(false, white_king, black_king, push)];
+        }
+        if pawn.rank() == Rank::Second && push != white_king && push != black_king {
+            let double = push
+                .offset(8)
+                .expect("Pawns on the second rank can advance twice");
+            successors |= results[index(false, white_king, black_king, double)];
+        }
+    } else {
+        for to in attacks::king_attacks(black_king) {
+            successors |= results[index(true, white_king, to, pawn)];
+        }
+    }
+
+    if successors & good != 0 {
+        good
+    } else if successors & UNKNOWN != 0 {
+        UNKNOWN
+    } else {
+        bad
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_king_in_front_of_pawn_wins() {
+        // The king on the sixth rank in front of its pawn wins with either side to move
+        assert!(probe(true, Square::D6, Square::D8, Square::D5));
+        assert!(probe(false, Square::D6, Square::D8, Square::D5));
+    }
+
+    #[test]
+    fn test_opposition_decides() {
+        // With the kings facing each other in front of the pawn, the side to move loses the
+        // opposition
+        assert!(!probe(true, Square::D5, Square::D7, Square::D4));
+        assert!(probe(false, Square::D5, Square::D7, Square::D4));
+    }
+
+    #[test]
+    fn test_rook_pawn_with_king_in_corner_draws() {
+        assert!(!probe(true, Square::H1, Square::A8, Square::A5));
+        assert!(!probe(true, Square::C6, Square::A8, Square::A6));
+    }
+
+    #[test]
+    fn test_pawn_outruns_distant_king() {
+        assert!(probe(true, Square::H1, Square::H8, Square::A4));
+        assert!(!probe(false, Square::H1, Square::D6, Square::A4));
+    }
+}
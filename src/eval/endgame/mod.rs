@@ -0,0 +1,230 @@
+pub mod kpk;
+
+use crate::{
+    board::Board,
+    eval::material::{material_score, MaterialSignature},
+};
+use shakmaty::{ByColor, ByRole, Color, File, Role, Square};
+use std::{collections::HashMap, sync::OnceLock};
+
+/// Score of an endgame known to be won, above any ordinary evaluation but well below mate scores.
+pub const KNOWN_WIN: i32 = 10000;
+
+// Bonuses for driving the losing king toward the edge and bringing the winning king close to it
+const PUSH_TO_EDGE: i32 = 20;
+const PUSH_CLOSE: i32 = 10;
+const PUSH_TO_CORNER: i32 = 40;
+
+/// Evaluates an endgame from the point of view of the stronger side.
+pub type EndgameFn = fn(&Board, Color) -> i32;
+
+/// Endgames with a dedicated evaluator, by material signature, with the stronger side.
+static ENDGAMES: OnceLock<HashMap<MaterialSignature, (Color, EndgameFn)>> = OnceLock::new();
+
+fn endgames() -> HashMap<MaterialSignature, (Color, EndgameFn)> {
+    let mut endgames = HashMap::new();
+    let mut add = |code: &str, evaluator: EndgameFn| {
+        for strong in Color::ALL {
+            endgames.insert(signature_of(code, strong), (strong, evaluator));
+        }
+    };
+
+    add("KQvK", mop_up);
+    add("KRvK", mop_up);
+    add("KBNvK", kbnk);
+    add("KPvK", kpk);
+    endgames
+}
+
+/// Builds the signature of an endgame given as the pieces of the stronger side and of the weaker
+/// side, such as "KBNvK".
+fn signature_of(code: &str, strong: Color) -> MaterialSignature {
+    let (strong_pieces, weak_pieces) = code.split_once('v').expect("Endgame codes contain a 'v'");
+    let mut material = ByColor::<ByRole<u8>>::default();
+    for (color, pieces) in [(strong, strong_pieces), (!strong, weak_pieces)] {
+        for c in pieces.chars() {
+            let role =
+                Role::from_char(c.to_ascii_lowercase()).expect("Invalid piece in endgame code");
+            *material.get_mut(color).get_mut(role) += 1;
+        }
+    }
+    MaterialSignature(material)
+}
+
+/// Evaluates the position with the evaluator for its endgame from White's point of view, if there
+/// is one. Otherwise a bare king against a queen or rook is evaluated by mop-up.
+pub fn probe(pos: &Board, signature: &MaterialSignature) -> Option<i32> {
+    let (strong, evaluator) = match ENDGAMES.get_or_init(endgames).get(signature) {
+        Some(&entry) => entry,
+        None => {
+            let strong = Color::ALL.into_iter().find(|&color| {
+                is_bare_king(signature, !color)
+                    && signature.count(color, Role::Queen) + signature.count(color, Role::Rook) > 0
+            })?;
+            (strong, mop_up as EndgameFn)
+        }
+    };
+
+    let score = evaluator(pos, strong);
+    Some(strong.fold_wb(score, -score))
+}
+
+fn is_bare_king(signature: &MaterialSignature, color: Color) -> bool {
+    signature.non_pawn_material(color) == 0 && signature.count(color, Role::Pawn) == 0
+}
+
+/// Material of a side, pawns included.
+fn material_of(pos: &Board, color: Color) -> i32 {
+    Role::ALL
+        .into_iter()
+        .map(|role| pos.board().by_piece(role.of(color)).count() as i32 * material_score(role))
+        .sum()
+}
+
+/// Distance of a square from the centre, 0 on the four central squares and 6 in the corners.
+fn center_distance(square: Square) -> i32 {
+    let file = square.file().to_u32() as i32;
+    let rank = square.rank().to_u32() as i32;
+    (2 * file - 7).abs() / 2 + (2 * rank - 7).abs() / 2
+}
+
+fn kings(pos: &Board, strong: Color) -> (Square, Square) {
+    let board = pos.board();
+    (
+        board.king_of(strong).expect("Positions have kings"),
+        board.king_of(!strong).expect("Positions have kings"),
+    )
+}
+
+/// Evaluates a won endgame against a bare king, where mate needs the losing king on the edge and
+/// the winning king close to it.
+fn mop_up(pos: &Board, strong: Color) -> i32 {
+    let (strong_king, weak_king) = kings(pos, strong);
+    KNOWN_WIN
+        + material_of(pos, strong)
+        + PUSH_TO_EDGE * center_distance(weak_king)
+        + PUSH_CLOSE * (7 - strong_king.distance(weak_king) as i32)
+}
+
+/// Evaluates bishop and knight against king, where mate is only possible in a corner of the
+/// bishop's colour.
+fn kbnk(pos: &Board, strong: Color) -> i32 {
+    let (strong_king, weak_king) = kings(pos, strong);
+    let bishop = pos
+        .board()
+        .by_piece(Role::Bishop.of(strong))
+        .first()
+        .expect("The stronger side has a bishop");
+    let corners = if bishop.is_dark() {
+        [Square::A1, Square::H8]
+    } else {
+        [Square::H1, Square::A8]
+    };
+    let corner_distance = corners
+        .into_iter()
+        .map(|corner| corner.distance(weak_king) as i32)
+        .min()
+        .unwrap_or_default();
+
+    KNOWN_WIN
+        + material_of(pos, strong)
+        + PUSH_TO_CORNER * (7 - corner_distance)
+        + PUSH_CLOSE * (7 - strong_king.distance(weak_king) as i32)
+}
+
+/// Evaluates king and pawn against king exactly from the bitbase, seen as White with the pawn on
+/// files A to D.
+fn kpk(pos: &Board, strong: Color) -> i32 {
+    let (strong_king, weak_king) = kings(pos, strong);
+    let pawn = pos
+        .board()
+        .by_piece(Role::Pawn.of(strong))
+        .first()
+        .expect("The stronger side has a pawn");
+
+    let normalize = |square: Square| {
+        let square = strong.fold_wb(square, square.flip_vertical());
+        if pawn.file() >= File::E {
+            square.flip_horizontal()
+        } else {
+            square
+        }
+    };
+    let pawn = normalize(pawn);
+
+    if kpk::probe(
+        pos.turn() == strong,
+        normalize(strong_king),
+        normalize(weak_king),
+        pawn,
+    ) {
+        KNOWN_WIN + material_score(Role::Pawn) + 10 * pawn.rank().to_u32() as i32
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, CastlingMode, Chess};
+    use std::str::FromStr;
+
+    fn parse_board(fen_str: &str) -> Board {
+        let pos: Chess = Fen::from_str(fen_str)
+            .unwrap()
+            .into_position(CastlingMode::Standard)
+            .unwrap();
+        Board::from(&pos)
+    }
+
+    fn probe_fen(fen_str: &str) -> Option<i32> {
+        let board = parse_board(fen_str);
+        probe(&board, &MaterialSignature::new(board.board()))
+    }
+
+    #[test]
+    fn test_registry_finds_both_colors() {
+        assert!(probe_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap() > KNOWN_WIN);
+        assert!(probe_fen("3qk3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap() < -KNOWN_WIN);
+        assert_eq!(
+            probe_fen("4k3/8/8/8/8/8/8/2BQK3 w - - 0 1").map(|s| s > 0),
+            Some(true)
+        );
+        assert_eq!(probe_fen("4k3/p7/8/8/8/8/8/3QK3 w - - 0 1"), None);
+        assert_eq!(
+            probe(
+                &Board::default(),
+                &MaterialSignature::new(Board::default().board())
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mop_up_prefers_king_on_edge() {
+        let edge = probe_fen("7k/8/8/8/8/8/8/3RK3 w - - 0 1").unwrap();
+        let center = probe_fen("8/8/8/4k3/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert!(edge > center);
+    }
+
+    #[test]
+    fn test_kbnk_prefers_bishop_corner() {
+        // The dark-squared bishop mates in a1 or h8
+        let right = probe_fen("7k/8/8/8/8/8/8/2B1KN2 w - - 0 1").unwrap();
+        let wrong = probe_fen("k7/8/8/8/8/8/8/2B1KN2 w - - 0 1").unwrap();
+        assert!(right > wrong);
+    }
+
+    #[test]
+    fn test_kpk() {
+        // The king in front of its pawn wins with the opposition
+        assert!(probe_fen("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1").unwrap() > KNOWN_WIN);
+        // Mirrored for Black
+        assert!(probe_fen("8/8/8/8/4p3/4k3/8/4K3 w - - 0 1").unwrap() < -KNOWN_WIN);
+        // The defending king reaches the corner in front of the rook pawn
+        assert_eq!(probe_fen("k7/8/8/P7/8/8/8/7K w - - 0 1"), Some(0));
+        // The pawn is lost
+        assert_eq!(probe_fen("8/8/8/8/8/8/3kP3/7K b - - 0 1"), Some(0));
+    }
+}
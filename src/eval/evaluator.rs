@@ -0,0 +1,175 @@
+use crate::{
+    board::Board,
+    eval::{evaluate, pawns::PawnTable},
+};
+use shakmaty::Move;
+use std::{fmt, str::FromStr};
+
+/// Static evaluation used by the search, with hooks to keep incremental state in step with the
+/// board as moves are played and undone.
+pub trait Evaluator {
+    /// Evaluates the position from the point of view of the side to move.
+    fn evaluate(&mut self, board: &Board) -> i32;
+
+    /// Called before a move is played on the board, or a null move if there is none.
+    fn play(&mut self, _board: &Board, _mv: Option<Move>) {}
+
+    /// Called after the last move has been undone on the board.
+    fn undo(&mut self, _board: &Board) {}
+
+    /// Forgets what was cached from earlier searches.
+    fn clear(&mut self) {}
+}
+
+/// The full evaluation, with the pawn structure cached across positions.
+#[derive(Default)]
+pub struct StandardEvaluator {
+    pub pawns: PawnTable,
+}
+
+impl Evaluator for StandardEvaluator {
+    fn evaluate(&mut self, board: &Board) -> i32 {
+        evaluate(board, &mut self.pawns)
+    }
+
+    fn clear(&mut self) {
+        self.pawns.clear();
+    }
+}
+
+/// Material and piece-square tables only, as kept up to date by the board.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaterialEvaluator;
+
+impl Evaluator for MaterialEvaluator {
+    fn evaluate(&mut self, board: &Board) -> i32 {
+        let accumulator = board.accumulator();
+        let score = accumulator
+            .phase
+            .taper(accumulator.middlegame, accumulator.endgame);
+        board.turn().fold_wb(score, -score)
+    }
+}
+
+/// The evaluators that can be chosen at runtime.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EvaluatorKind {
+    #[default]
+    Standard,
+    Material,
+}
+
+impl EvaluatorKind {
+    pub const ALL: [EvaluatorKind; 2] = [EvaluatorKind::Standard, EvaluatorKind::Material];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EvaluatorKind::Standard => "standard",
+            EvaluatorKind::Material => "material",
+        }
+    }
+}
+
+impl fmt::Display for EvaluatorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for EvaluatorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EvaluatorKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown evaluator '{s}'"))
+    }
+}
+
+/// An evaluator chosen at runtime, for comparing evaluators without rebuilding the engine.
+pub enum AnyEvaluator {
+    Standard(StandardEvaluator),
+    Material(MaterialEvaluator),
+}
+
+impl AnyEvaluator {
+    pub fn new(kind: EvaluatorKind) -> Self {
+        match kind {
+            EvaluatorKind::Standard => AnyEvaluator::Standard(StandardEvaluator::default()),
+            EvaluatorKind::Material => AnyEvaluator::Material(MaterialEvaluator),
+        }
+    }
+
+    pub fn kind(&self) -> EvaluatorKind {
+        match self {
+            AnyEvaluator::Standard(_) => EvaluatorKind::Standard,
+            AnyEvaluator::Material(_) => EvaluatorKind::Material,
+        }
+    }
+}
+
+impl Default for AnyEvaluator {
+    fn default() -> Self {
+        AnyEvaluator::new(EvaluatorKind::default())
+    }
+}
+
+impl Evaluator for AnyEvaluator {
+    fn evaluate(&mut self, board: &Board) -> i32 {
+        match self {
+            AnyEvaluator::Standard(evaluator) => evaluator.evaluate(board),
+            AnyEvaluator::Material(evaluator) => evaluator.evaluate(board),
+        }
+    }
+
+    fn play(&mut self, board: &Board, mv: Option<Move>) {
+        match self {
+            AnyEvaluator::Standard(evaluator) => evaluator.play(board, mv),
+            AnyEvaluator::Material(evaluator) => evaluator.play(board, mv),
+        }
+    }
+
+    fn undo(&mut self, board: &Board) {
+        match self {
+            AnyEvaluator::Standard(evaluator) => evaluator.undo(board),
+            AnyEvaluator::Material(evaluator) => evaluator.undo(board),
+        }
+    }
+
+    fn clear(&mut self) {
+        match self {
+            AnyEvaluator::Standard(evaluator) => evaluator.clear(),
+            AnyEvaluator::Material(evaluator) => evaluator.clear(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_from_name() {
+        for kind in EvaluatorKind::ALL {
+            assert_eq!(kind.name().parse(), Ok(kind));
+        }
+        assert_eq!("Material".parse(), Ok(EvaluatorKind::Material));
+        assert!("nnue".parse::<EvaluatorKind>().is_err());
+    }
+
+    #[test]
+    fn test_any_evaluator_kind() {
+        assert_eq!(AnyEvaluator::default().kind(), EvaluatorKind::Standard);
+        for kind in EvaluatorKind::ALL {
+            assert_eq!(AnyEvaluator::new(kind).kind(), kind);
+        }
+    }
+
+    #[test]
+    fn test_start_position_is_even() {
+        let board = Board::default();
+        assert_eq!(MaterialEvaluator.evaluate(&board), 0);
+        assert_eq!(AnyEvaluator::default().evaluate(&board), 0);
+    }
+}
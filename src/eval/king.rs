@@ -0,0 +1,145 @@
+use crate::eval::pawns::ranks_ahead;
+use shakmaty::{attacks, Bitboard, Board, Color, File, Role, Square};
+
+/// Penalty by the distance in ranks from the king to the nearest pawn of its side in front of it
+/// on a file next to it, with 0 standing for no pawn at all.
+const PAWN_SHIELD: [i32; 4] = [-30, 0, -10, -20];
+
+/// Penalty by the distance in ranks from the king to the nearest enemy pawn advancing on a file
+/// next to it, with 0 standing for no pawn close enough to matter.
+const PAWN_STORM: [i32; 4] = [0, -10, -30, -15];
+
+// For every file next to the king without any pawns
+const OPEN_FILE: i32 = -20;
+// For every file next to the king with enemy pawns only
+const HALF_OPEN_FILE: i32 = -10;
+
+/// Attack units by the role of a piece, counted for every square of the king zone it attacks.
+const ATTACK_UNITS: [i32; 6] = [0, 2, 2, 3, 5, 0];
+
+/// Most the attack units can cost, so a single attack cannot outweigh all material.
+const MAX_KING_DANGER: i32 = 600;
+
+/// Penalty for every square a queen on the king square could reach, a measure of how exposed the
+/// king is to sliders.
+const VIRTUAL_MOBILITY: i32 = -3;
+
+/// Evaluates the safety of both kings from White's point of view. The terms only apply to the
+/// middlegame, where attacks on the king decide games, so they fade out with the material.
+pub fn king_safety(board: &Board) -> (i32, i32) {
+    let mut middlegame = 0;
+    for color in Color::ALL {
+        if let Some(king) = board.king_of(color) {
+            middlegame += color.fold_wb(1, -1) * king_safety_of(board, color, king);
+        }
+    }
+    (middlegame, 0)
+}
+
+/// Evaluates the safety of the king of one side.
+fn king_safety_of(board: &Board, color: Color, king: Square) -> i32 {
+    let exposed = attacks::queen_attacks(king, board.occupied()) & !board.by_color(color);
+    pawn_cover(board, color, king)
+        + king_danger(board, color, king)
+        + VIRTUAL_MOBILITY * exposed.count() as i32
+}
+
+/// Scores the pawn shield, the enemy pawn storm and the open files on the king's file and the
+/// files next to it.
+fn pawn_cover(board: &Board, color: Color, king: Square) -> i32 {
+    let ours = board.pawns() & board.by_color(color);
+    let theirs = board.pawns() & board.by_color(!color);
+    let ahead = ranks_ahead(color, king.rank());
+
+    // A king on the edge is covered by the three files closest to it
+    let center = king.file().to_u32().clamp(1, 6);
+    let mut score = 0;
+    for file in (center - 1..=center + 1).map(File::new) {
+        let file = Bitboard::from_file(file);
+        let distance = |pawns: Bitboard| {
+            nearest(color, pawns & file & ahead).map_or(0, |pawn| pawn.rank().distance(king.rank()))
+        };
+
+        score += PAWN_SHIELD[distance(ours).min(3) as usize];
+        score += PAWN_STORM
+            .get(distance(theirs) as usize)
+            .copied()
+            .unwrap_or(0);
+
+        if (ours & file).is_empty() {
+            score += if (theirs & file).is_empty() {
+                OPEN_FILE
+            } else {
+                HALF_OPEN_FILE
+            };
+        }
+    }
+    score
+}
+
+/// Adds up attack units of the enemy pieces hitting the squares around the king. The penalty
+/// grows with the square of the units, since a lone attacker is rarely dangerous but several
+/// coordinated ones are.
+fn king_danger(board: &Board, color: Color, king: Square) -> i32 {
+    let zone = attacks::king_attacks(king).with(king);
+
+    let mut attackers = 0;
+    let mut units = 0;
+    for role in [Role::Knight, Role::Bishop, Role::Rook, Role::Queen] {
+        let piece = role.of(!color);
+        for square in board.by_piece(piece) {
+            let hits = attacks::attacks(square, piece, board.occupied()) & zone;
+            if hits.any() {
+                attackers += 1;
+                units += ATTACK_UNITS[role as usize - 1] * hits.count() as i32;
+            }
+        }
+    }
+
+    if attackers < 2 {
+        return 0;
+    }
+    -(units * units / 2).min(MAX_KING_DANGER)
+}
+
+/// The square of a set closest to the side's own backrank.
+fn nearest(color: Color, squares: Bitboard) -> Option<Square> {
+    color.fold_wb(squares.first(), squares.last())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, CastlingMode, Chess, Position};
+    use std::str::FromStr;
+
+    fn parse_board(fen_str: &str) -> Board {
+        Fen::from_str(fen_str)
+            .unwrap()
+            .into_position::<Chess>(CastlingMode::Standard)
+            .unwrap()
+            .board()
+            .clone()
+    }
+
+    #[test]
+    fn test_start_position_is_balanced() {
+        assert_eq!(king_safety(Chess::default().board()), (0, 0));
+    }
+
+    #[test]
+    fn test_pawn_shield() {
+        // White castled behind an intact shield, against a king with its pawns pushed
+        let board = parse_board("6k1/5p2/6pp/8/8/8/5PPP/6K1 w - - 0 1");
+        assert!(king_safety(&board).0 > 0);
+    }
+
+    #[test]
+    fn test_attacked_king() {
+        // Queen and knight both hit squares next to the black king
+        let attacked = parse_board("6k1/5ppp/8/3Q2N1/8/8/5PPP/6K1 w - - 0 1");
+        let quiet = parse_board("6k1/5ppp/8/8/8/2N5/5PPP/3Q2K1 w - - 0 1");
+        assert!(king_danger(&attacked, Color::Black, Square::G8) < 0);
+        assert_eq!(king_danger(&quiet, Color::Black, Square::G8), 0);
+    }
+}
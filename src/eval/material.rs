@@ -1,4 +1,9 @@
-use shakmaty::Role;
+use shakmaty::{Board, ByColor, ByRole, Color, Role};
+
+// Change in the value of a piece for every pawn of its side above five: knights gain from
+// closed positions, while rooks need open files
+const KNIGHT_PAWN_ADJUSTMENT: i32 = 6;
+const ROOK_PAWN_ADJUSTMENT: i32 = -12;
 
 /// Evaluates the material score of a piece.
 #[inline(always)]
@@ -12,3 +17,77 @@ pub const fn material_score(role: Role) -> i32 {
         Role::King => 0,
     }
 }
+
+/// Number of pieces of each role on both sides, identifying the kind of position for the material
+/// dependent parts of the evaluation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MaterialSignature(pub ByColor<ByRole<u8>>);
+
+impl MaterialSignature {
+    pub fn new(board: &Board) -> MaterialSignature {
+        MaterialSignature(board.material())
+    }
+
+    pub fn count(&self, color: Color, role: Role) -> i32 {
+        *self.0.get(color).get(role) as i32
+    }
+
+    /// Material of a side apart from its pawns.
+    pub fn non_pawn_material(&self, color: Color) -> i32 {
+        [Role::Knight, Role::Bishop, Role::Rook, Role::Queen]
+            .into_iter()
+            .map(|role| self.count(color, role) * material_score(role))
+            .sum()
+    }
+}
+
+/// Evaluates second-order material terms from White's point of view, where the value of a piece
+/// depends on the rest of the material.
+pub fn imbalance(signature: &MaterialSignature) -> (i32, i32) {
+    let mut score = 0;
+    for color in Color::ALL {
+        let extra_pawns = signature.count(color, Role::Pawn) - 5;
+        let adjustment =
+            signature.count(color, Role::Knight) * extra_pawns * KNIGHT_PAWN_ADJUSTMENT
+                + signature.count(color, Role::Rook) * extra_pawns * ROOK_PAWN_ADJUSTMENT;
+        score += color.fold_wb(1, -1) * adjustment;
+    }
+    (score, score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, CastlingMode, Chess, Position};
+    use std::str::FromStr;
+
+    fn signature_of(fen_str: &str) -> MaterialSignature {
+        let pos: Chess = Fen::from_str(fen_str)
+            .unwrap()
+            .into_position(CastlingMode::Standard)
+            .unwrap();
+        MaterialSignature::new(pos.board())
+    }
+
+    #[test]
+    fn test_start_position() {
+        let signature = MaterialSignature::new(Chess::default().board());
+        assert_eq!(signature.count(Color::White, Role::Pawn), 8);
+        assert_eq!(
+            signature.non_pawn_material(Color::Black),
+            2 * 320 + 2 * 330 + 2 * 500 + 900
+        );
+        assert_eq!(imbalance(&signature), (0, 0));
+    }
+
+    #[test]
+    fn test_knight_gains_with_pawns() {
+        // Knight against rook with all pawns, so the knight gains and the rook loses
+        let signature = signature_of("3rk3/pppppppp/8/8/8/8/PPPPPPPP/1N2K3 w - - 0 1");
+        let (middlegame, _) = imbalance(&signature);
+        assert_eq!(
+            middlegame,
+            3 * KNIGHT_PAWN_ADJUSTMENT - 3 * ROOK_PAWN_ADJUSTMENT
+        );
+    }
+}
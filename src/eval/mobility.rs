@@ -0,0 +1,153 @@
+use crate::eval::{
+    attacks::AttackMap,
+    pawns::{adjacent_files, ranks_ahead},
+};
+use shakmaty::{attacks, Bitboard, Board, Color, Rank, Role};
+
+// Bonus by the number of squares a piece can move to, in the middlegame and the endgame
+#[rustfmt::skip]
+const KNIGHT_MOBILITY: [i32; 9] = [-40, -25, -10, -3, 3, 8, 13, 18, 22];
+#[rustfmt::skip]
+const KNIGHT_ENDGAME_MOBILITY: [i32; 9] = [-50, -35, -15, -5, 3, 8, 13, 16, 18];
+
+#[rustfmt::skip]
+const BISHOP_MOBILITY: [i32; 14] = [-35, -20, -8, 0, 8, 15, 20, 24, 28, 32, 35, 38, 40, 42];
+#[rustfmt::skip]
+const BISHOP_ENDGAME_MOBILITY: [i32; 14] = [-45, -25, -10, 0, 8, 15, 20, 25, 30, 34, 37, 40, 42, 44];
+
+#[rustfmt::skip]
+const ROOK_MOBILITY: [i32; 15] = [-25, -15, -8, -4, 0, 3, 6, 10, 13, 16, 19, 21, 23, 25, 27];
+#[rustfmt::skip]
+const ROOK_ENDGAME_MOBILITY: [i32; 15] = [-40, -20, -5, 5, 15, 22, 28, 34, 40, 45, 50, 54, 57, 60, 62];
+
+#[rustfmt::skip]
+const QUEEN_MOBILITY: [i32; 28] = [
+    -20, -12,  -6,  -2,   0,   2,   4,   6,   8,  10,  12,  13,  14,  15,
+     16,  17,  18,  19,  20,  21,  22,  23,  24,  25,  26,  27,  28,  29,
+];
+#[rustfmt::skip]
+const QUEEN_ENDGAME_MOBILITY: [i32; 28] = [
+    -30, -20, -12,  -6,  -2,   2,   6,  10,  14,  18,  22,  25,  28,  31,
+     34,  36,  38,  40,  42,  44,  46,  48,  50,  52,  53,  54,  55,  56,
+];
+
+// Bonus for a minor piece on a square defended by a pawn that no enemy pawn can ever attack
+const KNIGHT_OUTPOST: (i32, i32) = (30, 20);
+const BISHOP_OUTPOST: (i32, i32) = (15, 10);
+
+/// Evaluates the mobility of the pieces and the minor pieces on outposts from White's point of
+/// view.
+pub fn mobility(board: &Board, attacks: &AttackMap) -> (i32, i32) {
+    let mut middlegame = 0;
+    let mut endgame = 0;
+    for color in Color::ALL {
+        let sign = color.fold_wb(1, -1);
+        let (color_middlegame, color_endgame) = mobility_of(board, attacks, color);
+        middlegame += sign * color_middlegame;
+        endgame += sign * color_endgame;
+    }
+    (middlegame, endgame)
+}
+
+/// Evaluates the pieces of one side.
+fn mobility_of(board: &Board, attack_map: &AttackMap, color: Color) -> (i32, i32) {
+    let their_pawns = board.pawns() & board.by_color(!color);
+
+    // Squares guarded by an enemy pawn are no real option for a piece
+    let available = !board.by_color(color) & !attack_map.by_role(!color, Role::Pawn);
+    let outposts = outposts(color, attack_map.by_role(color, Role::Pawn), their_pawns);
+
+    let mut middlegame = 0;
+    let mut endgame = 0;
+    for role in [Role::Knight, Role::Bishop, Role::Rook, Role::Queen] {
+        let piece = role.of(color);
+        let (curve, endgame_curve): (&[i32], &[i32]) = match role {
+            Role::Knight => (&KNIGHT_MOBILITY, &KNIGHT_ENDGAME_MOBILITY),
+            Role::Bishop => (&BISHOP_MOBILITY, &BISHOP_ENDGAME_MOBILITY),
+            Role::Rook => (&ROOK_MOBILITY, &ROOK_ENDGAME_MOBILITY),
+            _ => (&QUEEN_MOBILITY, &QUEEN_ENDGAME_MOBILITY),
+        };
+
+        for square in board.by_piece(piece) {
+            let moves = (attacks::attacks(square, piece, board.occupied()) & available).count();
+            middlegame += curve[moves];
+            endgame += endgame_curve[moves];
+
+            if outposts.contains(square) {
+                let (outpost_middlegame, outpost_endgame) = match role {
+                    Role::Knight => KNIGHT_OUTPOST,
+                    Role::Bishop => BISHOP_OUTPOST,
+                    _ => (0, 0),
+                };
+                middlegame += outpost_middlegame;
+                endgame += outpost_endgame;
+            }
+        }
+    }
+    (middlegame, endgame)
+}
+
+/// Squares in the enemy half defended by one of our pawns where no enemy pawn can ever attack a
+/// piece, since none is left on the files next to it further up the board.
+fn outposts(color: Color, defended: Bitboard, their_pawns: Bitboard) -> Bitboard {
+    let enemy_half = [Rank::Fourth, Rank::Fifth, Rank::Sixth]
+        .into_iter()
+        .fold(Bitboard::EMPTY, |half, rank| {
+            half | Bitboard::from_rank(color.relative_rank(rank))
+        });
+
+    let mut outposts = Bitboard::EMPTY;
+    for square in defended & enemy_half {
+        let attackers = adjacent_files(square.file()) & ranks_ahead(color, square.rank());
+        if (their_pawns & attackers).is_empty() {
+            outposts.add(square);
+        }
+    }
+    outposts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, CastlingMode, Chess, Position, Square};
+    use std::str::FromStr;
+
+    fn parse_board(fen_str: &str) -> Board {
+        Fen::from_str(fen_str)
+            .unwrap()
+            .into_position::<Chess>(CastlingMode::Standard)
+            .unwrap()
+            .board()
+            .clone()
+    }
+
+    #[test]
+    fn test_start_position_is_balanced() {
+        let board = Chess::default().board().clone();
+        assert_eq!(mobility(&board, &AttackMap::new(&board)), (0, 0));
+    }
+
+    #[test]
+    fn test_active_pieces_are_preferred() {
+        // A knight in the center against one on the rim, and an open bishop against a locked one
+        let active = parse_board("4k3/8/8/8/4N3/3B4/4P1P1/4K3 w - - 0 1");
+        let passive = parse_board("4k3/8/8/8/8/8/4P1P1/N3KB2 w - - 0 1");
+        let (active_middlegame, active_endgame) = mobility(&active, &AttackMap::new(&active));
+        let (passive_middlegame, passive_endgame) = mobility(&passive, &AttackMap::new(&passive));
+        assert!(active_middlegame > passive_middlegame);
+        assert!(active_endgame > passive_endgame);
+    }
+
+    #[test]
+    fn test_outposts() {
+        // Both squares are defended by the e4 pawn, but only the g-pawn can still chase a piece
+        // from f5, while the c-pawn has advanced past d5
+        let outposts = outposts(
+            Color::White,
+            attacks::pawn_attacks(Color::White, Square::E4),
+            Bitboard::from_square(Square::C4).with(Square::G7),
+        );
+        assert!(outposts.contains(Square::D5));
+        assert!(!outposts.contains(Square::F5));
+    }
+}
@@ -1,37 +1,75 @@
+pub mod accumulator;
+pub mod attacks;
+pub mod endgame;
+pub mod evaluator;
+pub mod king;
 pub mod material;
+pub mod mobility;
 pub mod order;
+pub mod pawns;
 pub mod phase;
+pub mod pieces;
 pub mod pst;
+pub mod scaling;
+pub mod see;
+pub mod threats;
 
-use crate::eval::{material::material_score, phase::Phase, pst::position_score};
-use shakmaty::{Chess, Position};
+use crate::{
+    board::Board,
+    eval::{
+        accumulator::Accumulator,
+        attacks::AttackMap,
+        king::king_safety,
+        material::{imbalance, MaterialSignature},
+        mobility::mobility,
+        pawns::{pawn_score, PawnTable},
+        pieces::piece_terms,
+        scaling::{scale_factor, SCALE_NORMAL},
+        threats::threats,
+    },
+};
+use shakmaty::Color;
 
-/// Evaluates the current position.
-pub fn evaluate(pos: &Chess, ply: u8) -> i32 {
-    // First check for termination
-    if pos.is_checkmate() {
-        // Add one per ply to express mate in x moves (offset +1 due to i32 range assymetry).
-        return i32::MIN + 1 + ply as i32;
-    } else if pos.is_game_over() {
-        // Draw
-        return 0;
-    }
-
-    // Calculate phase
-    let phase = Phase::new(pos);
+/// Evaluates the current position statically. Terminal positions are left to the search, which
+/// finds mate and stalemate from the moves it generates.
+pub fn evaluate(pos: &Board, pawns: &mut PawnTable) -> i32 {
+    // Material and piece placement are kept up to date by the board
+    let accumulator = pos.accumulator();
+    debug_assert_eq!(
+        *accumulator,
+        Accumulator::new(pos.board()),
+        "Incremental evaluation diverged from a full recompute"
+    );
 
-    // Accumulator
-    let mut diff = 0;
-
-    for (square, piece) in pos.board().iter() {
-        let score = material_score(piece.role) + position_score(piece, square, &phase);
+    let board = pos.board();
+    let signature = MaterialSignature::new(board);
+    // Endgames with dedicated knowledge replace the general evaluation
+    if let Some(score) = endgame::probe(pos, &signature) {
+        return pos.turn().fold_wb(score, -score);
+    }
 
-        if piece.color == pos.turn() {
-            diff += score;
-        } else {
-            diff -= score;
-        }
+    let attacks = AttackMap::new(board);
+    let mut middlegame = accumulator.middlegame;
+    let mut endgame = accumulator.endgame;
+    for (term_middlegame, term_endgame) in [
+        pawn_score(board, pos.pawn_hash(), pawns),
+        king_safety(board),
+        mobility(board, &attacks),
+        piece_terms(board),
+        threats(board, &attacks),
+        imbalance(&signature),
+    ] {
+        middlegame += term_middlegame;
+        endgame += term_endgame;
     }
 
-    diff
+    // Endgames the side ahead can hardly win are pulled toward a draw
+    let score = accumulator.phase.taper(middlegame, endgame);
+    let strong = if score > 0 {
+        Color::White
+    } else {
+        Color::Black
+    };
+    let score = score * scale_factor(pos, &signature, strong) / SCALE_NORMAL;
+    pos.turn().fold_wb(score, -score)
 }
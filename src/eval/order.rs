@@ -1,11 +1,51 @@
-use shakmaty::{Move, MoveList};
+use shakmaty::{Board, Move, MoveList};
 
-use crate::eval::material::material_score;
+use crate::eval::{material::material_score, see::see};
 
 /// Sorts a list of moves in order of how promising they are.
 /// Start index allows for exluding a prefix of the list.
-pub fn order(mut moves: MoveList, start_index: usize) -> MoveList {
-    moves[start_index..].sort_by_key(|mv| -match mv {
+pub fn order(mut moves: MoveList, start_index: usize, board: &Board) -> MoveList {
+    moves[start_index..].sort_by_cached_key(|mv| -score(mv, board));
+    moves
+}
+
+/// Sorts a list of moves like `order`, returning each with its static exchange evaluation (0 for
+/// moves that capture nothing), so the search can prune by it without evaluating it again.
+pub fn order_with_exchange(moves: MoveList, board: &Board) -> Vec<(Move, i32)> {
+    let mut moves: Vec<(Move, i32)> = moves
+        .into_iter()
+        .map(|mv| (mv, exchange(&mv, board)))
+        .collect();
+    moves.sort_by_key(|&(mv, exchange)| -score_with_exchange(&mv, exchange));
+    moves
+}
+
+/// Scores how promising a move is.
+fn score(mv: &Move, board: &Board) -> i32 {
+    score_with_exchange(mv, exchange(mv, board))
+}
+
+fn score_with_exchange(mv: &Move, exchange: i32) -> i32 {
+    // Captures losing material in the exchange come after quiet moves
+    if exchange < 0 {
+        return 4000 + exchange;
+    }
+
+    kind_score(mv)
+}
+
+/// Material won by a capture once all exchanges on its square are resolved.
+fn exchange(mv: &Move, board: &Board) -> i32 {
+    if mv.is_capture() {
+        see(board, *mv)
+    } else {
+        0
+    }
+}
+
+/// Scores a move by its kind alone, ranking captures by MVV-LVA.
+pub fn kind_score(mv: &Move) -> i32 {
+    match mv {
         // Capture AND promote
         Move::Normal {
             capture: Some(_),
@@ -36,16 +76,20 @@ pub fn order(mut moves: MoveList, start_index: usize) -> MoveList {
 
         // Not applicable in regular chess
         Move::Put { .. } => 0,
-    });
-    moves
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use shakmaty::{Move, Role, Square};
+    use std::str::FromStr;
 
     use super::*;
 
+    fn parse_board(board_fen: &str) -> Board {
+        Board::from_str(board_fen).unwrap()
+    }
+
     #[test]
     fn test_captures_first() {
         let mut moves = MoveList::new();
@@ -66,12 +110,68 @@ mod tests {
             promotion: None,
         });
 
-        moves = order(moves, 0);
+        moves = order(moves, 0, &parse_board("4k3/8/1n6/B7/8/8/8/4K3"));
 
         assert_eq!(moves[0].capture(), Some(Role::Knight));
         assert_eq!(moves[1].capture(), None);
     }
 
+    #[test]
+    fn test_losing_captures_after_quiet_moves() {
+        let mut moves = MoveList::new();
+
+        moves.push(Move::Normal {
+            role: Role::Queen,
+            from: Square::D1,
+            capture: Some(Role::Pawn),
+            to: Square::D5,
+            promotion: None,
+        });
+
+        moves.push(Move::Normal {
+            role: Role::Queen,
+            from: Square::D1,
+            capture: None,
+            to: Square::D2,
+            promotion: None,
+        });
+
+        // The pawn on d5 is defended by the pawn on e6
+        moves = order(moves, 0, &parse_board("4k3/8/4p3/3p4/8/8/8/3QK3"));
+
+        assert_eq!(moves[0].capture(), None);
+        assert_eq!(moves[1].capture(), Some(Role::Pawn));
+    }
+
+    #[test]
+    fn test_order_with_exchange() {
+        let mut moves = MoveList::new();
+
+        moves.push(Move::Normal {
+            role: Role::Queen,
+            from: Square::D1,
+            capture: Some(Role::Pawn),
+            to: Square::D5,
+            promotion: None,
+        });
+
+        moves.push(Move::Normal {
+            role: Role::Queen,
+            from: Square::D1,
+            capture: None,
+            to: Square::D2,
+            promotion: None,
+        });
+
+        let board = parse_board("4k3/8/4p3/3p4/8/8/8/3QK3");
+        let ordered = order_with_exchange(moves.clone(), &board);
+
+        // The exchange comes with each move, in the same order as `order` gives
+        assert_eq!(ordered[0], (moves[1], 0));
+        assert_eq!(ordered[1], (moves[0], see(&board, moves[0])));
+        assert!(ordered[1].1 < 0);
+    }
+
     #[test]
     fn preserve_first_move() {
         let mut moves = MoveList::new();
@@ -100,7 +200,7 @@ mod tests {
             promotion: None,
         });
 
-        moves = order(moves, 1);
+        moves = order(moves, 1, &parse_board("4k3/8/1n6/B7/8/8/8/4K3"));
 
         assert_eq!(moves[0].capture(), None);
         assert_eq!(moves[1].capture(), Some(Role::Knight));
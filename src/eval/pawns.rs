@@ -0,0 +1,306 @@
+use shakmaty::{attacks, zobrist::Zobrist64, Bitboard, Board, Color, File, Rank, Square};
+
+/// Bonus for a passed pawn by relative rank, in the middlegame and the endgame.
+const PASSED_PAWN: [(i32, i32); 8] = [
+    (0, 0),
+    (5, 10),
+    (5, 15),
+    (10, 25),
+    (25, 45),
+    (45, 75),
+    (70, 110),
+    (0, 0),
+];
+
+/// Bonus for a passed pawn by relative rank if nothing stands on its way to promotion.
+const PASSED_FREE_PATH: [i32; 8] = [0, 0, 0, 5, 10, 20, 35, 0];
+
+/// Penalty for a passed pawn by relative rank if the square in front of it is occupied.
+const PASSED_BLOCKED: [i32; 8] = [0, 0, 0, 5, 10, 15, 20, 0];
+
+/// Weight of the king distances to the square in front of a passed pawn by relative rank.
+const PASSED_KING_DISTANCE: [i32; 8] = [0, 0, 0, 1, 2, 4, 6, 0];
+
+/// Bonus for a pawn defended by or standing next to a pawn of its side, by relative rank.
+const CONNECTED_PAWN: [(i32, i32); 8] = [
+    (0, 0),
+    (5, 0),
+    (8, 3),
+    (10, 5),
+    (15, 10),
+    (25, 20),
+    (40, 35),
+    (0, 0),
+];
+
+const ISOLATED_PAWN: (i32, i32) = (-10, -15);
+const DOUBLED_PAWN: (i32, i32) = (-10, -20);
+const BACKWARD_PAWN: (i32, i32) = (-8, -10);
+// For every pawn island beyond the first
+const PAWN_ISLAND: (i32, i32) = (-5, -10);
+
+/// Scores of the pawn structure from White's point of view, which depend on the pawns alone.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PawnEntry {
+    pub middlegame: i32,
+    pub endgame: i32,
+    // Passed pawns of both sides, which are scored further by the pieces around them
+    pub passed: Bitboard,
+}
+
+impl PawnEntry {
+    /// Evaluates the pawn structure of a board from scratch.
+    pub fn new(board: &Board) -> PawnEntry {
+        let mut entry = PawnEntry::default();
+        for color in Color::ALL {
+            let sign = color.fold_wb(1, -1);
+            let (middlegame, endgame, passed) = pawn_structure(board, color);
+            entry.middlegame += sign * middlegame;
+            entry.endgame += sign * endgame;
+            entry.passed |= passed;
+        }
+        entry
+    }
+}
+
+/// Pawn-structure scores by the Zobrist hash of the pawns alone. Pawns move rarely, so most
+/// positions of a search share their structure with one evaluated before.
+pub struct PawnTable {
+    table: Vec<Option<(Zobrist64, PawnEntry)>>,
+    size_power: u8,
+}
+
+impl PawnTable {
+    pub fn new(size_power: u8) -> Self {
+        Self {
+            table: vec![None; 1 << size_power],
+            size_power,
+        }
+    }
+
+    #[inline(always)]
+    fn index(&self, key: Zobrist64) -> usize {
+        (key.0 >> (64 - self.size_power)) as usize
+    }
+
+    /// Looks up the pawn structure of a board, evaluating and storing it on a miss.
+    pub fn probe(&mut self, board: &Board, key: Zobrist64) -> PawnEntry {
+        let index = self.index(key);
+        if let Some((zobrist, entry)) = self.table[index] {
+            if zobrist == key {
+                debug_assert_eq!(entry, PawnEntry::new(board));
+                return entry;
+            }
+        }
+
+        let entry = PawnEntry::new(board);
+        self.table[index] = Some((key, entry));
+        entry
+    }
+
+    pub fn clear(&mut self) {
+        self.table = vec![None; 1 << self.size_power]
+    }
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        PawnTable::new(16)
+    }
+}
+
+/// Evaluates the pawn structure and the passed pawns from White's point of view.
+pub fn pawn_score(board: &Board, key: Zobrist64, table: &mut PawnTable) -> (i32, i32) {
+    let entry = table.probe(board, key);
+    let mut middlegame = entry.middlegame;
+    let mut endgame = entry.endgame;
+
+    for color in Color::ALL {
+        let sign = color.fold_wb(1, -1);
+        let (passed_middlegame, passed_endgame) = passed_pawns(board, entry.passed, color);
+        middlegame += sign * passed_middlegame;
+        endgame += sign * passed_endgame;
+    }
+
+    (middlegame, endgame)
+}
+
+/// Scores the pawns of one side by their structure, and finds the passed ones.
+fn pawn_structure(board: &Board, color: Color) -> (i32, i32, Bitboard) {
+    let ours = board.pawns() & board.by_color(color);
+    let theirs = board.pawns() & board.by_color(!color);
+
+    let mut middlegame = 0;
+    let mut endgame = 0;
+    let mut add = |(mg, eg): (i32, i32)| {
+        middlegame += mg;
+        endgame += eg;
+    };
+
+    let mut passed = Bitboard::EMPTY;
+    for square in ours {
+        let rank = color.relative_rank(square.rank()).to_usize();
+        let file = Bitboard::from_file(square.file());
+        let adjacent = adjacent_files(square.file());
+        let ahead = ranks_ahead(color, square.rank());
+
+        // Only the front pawn of a file can be passed, the ones behind it are doubled
+        let doubled = (ours & file & ahead).any();
+        if !doubled && (theirs & (file | adjacent) & ahead).is_empty() {
+            passed.add(square);
+            add(PASSED_PAWN[rank]);
+        }
+        if doubled {
+            add(DOUBLED_PAWN);
+        }
+
+        let supported = (attacks::pawn_attacks(!color, square) & ours).any();
+        let phalanx = (ours & adjacent & Bitboard::from_rank(square.rank())).any();
+        if supported || phalanx {
+            add(CONNECTED_PAWN[rank]);
+        } else if (ours & adjacent).is_empty() {
+            add(ISOLATED_PAWN);
+        } else if (ours & adjacent & !ahead).is_empty() && stop_is_guarded(square, color, theirs) {
+            // Every neighbour has advanced past it, and an enemy pawn keeps it from catching up
+            add(BACKWARD_PAWN);
+        }
+    }
+
+    let islands = pawn_islands(ours);
+    add((
+        PAWN_ISLAND.0 * islands.saturating_sub(1) as i32,
+        PAWN_ISLAND.1 * islands.saturating_sub(1) as i32,
+    ));
+
+    (middlegame, endgame, passed)
+}
+
+/// Scores the passed pawns of one side by what stands in their way and by how close the kings
+/// are to them.
+fn passed_pawns(board: &Board, passed: Bitboard, color: Color) -> (i32, i32) {
+    let (Some(our_king), Some(their_king)) = (board.king_of(color), board.king_of(!color)) else {
+        return (0, 0);
+    };
+
+    let mut middlegame = 0;
+    let mut endgame = 0;
+    for square in passed & board.by_color(color) {
+        let rank = color.relative_rank(square.rank()).to_usize();
+        let Some(stop) = square.offset(color.fold_wb(8, -8)) else {
+            continue;
+        };
+
+        if board.occupied().contains(stop) {
+            middlegame -= PASSED_BLOCKED[rank];
+            endgame -= PASSED_BLOCKED[rank];
+        } else if (ranks_ahead(color, square.rank())
+            & Bitboard::from_file(square.file())
+            & board.occupied())
+        .is_empty()
+        {
+            endgame += PASSED_FREE_PATH[rank];
+        }
+
+        // In the endgame the enemy king has to catch the pawn, while ours escorts it
+        let their_distance = their_king.distance(stop).min(5) as i32;
+        let our_distance = our_king.distance(stop).min(5) as i32;
+        endgame += PASSED_KING_DISTANCE[rank] * (5 * their_distance - 2 * our_distance);
+    }
+
+    (middlegame, endgame)
+}
+
+/// Tests whether the square in front of a pawn is attacked by an enemy pawn.
+fn stop_is_guarded(square: Square, color: Color, theirs: Bitboard) -> bool {
+    square
+        .offset(color.fold_wb(8, -8))
+        .is_some_and(|stop| (attacks::pawn_attacks(color, stop) & theirs).any())
+}
+
+/// Number of groups of pawns on adjacent files.
+fn pawn_islands(pawns: Bitboard) -> u32 {
+    let files = File::ALL
+        .iter()
+        .filter(|&&file| (pawns & Bitboard::from_file(file)).any())
+        .fold(0u8, |files, &file| files | 1 << file.to_u32());
+
+    // Count the files starting an island
+    (files & !(files << 1)).count_ones()
+}
+
+/// Squares on the files next to a file.
+pub(crate) fn adjacent_files(file: File) -> Bitboard {
+    [file.offset(-1), file.offset(1)]
+        .into_iter()
+        .flatten()
+        .fold(Bitboard::EMPTY, |files, file| {
+            files | Bitboard::from_file(file)
+        })
+}
+
+/// Squares on the ranks in front of a rank, as seen by the given side.
+pub(crate) fn ranks_ahead(color: Color, rank: Rank) -> Bitboard {
+    let rank = rank.to_u32();
+    Bitboard(match color {
+        Color::White => u64::MAX.checked_shl(8 * (rank + 1)).unwrap_or(0),
+        Color::Black => !u64::MAX.checked_shl(8 * rank).unwrap_or(0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, CastlingMode, Chess, Position, Role};
+    use std::str::FromStr;
+
+    fn parse_board(fen_str: &str) -> Board {
+        Fen::from_str(fen_str)
+            .unwrap()
+            .into_position::<Chess>(CastlingMode::Standard)
+            .unwrap()
+            .board()
+            .clone()
+    }
+
+    #[test]
+    fn test_passed_pawns() {
+        // The d5 pawn is passed, the a2 pawn faces the b7 pawn and h-pawns block each other
+        let board = parse_board("4k3/1p5p/8/3P4/8/8/P6P/4K3 w - - 0 1");
+        let entry = PawnEntry::new(&board);
+        assert_eq!(entry.passed, Bitboard::from_square(Square::D5));
+    }
+
+    #[test]
+    fn test_weak_pawns_are_penalized() {
+        // Same material, but doubled and isolated pawns for White
+        let weak = parse_board("4k3/ppp5/8/8/8/2P5/P1P5/4K3 w - - 0 1");
+        let healthy = parse_board("4k3/ppp5/8/8/8/8/PPP5/4K3 w - - 0 1");
+        assert!(PawnEntry::new(&weak).endgame < PawnEntry::new(&healthy).endgame);
+
+        let white_pawns = |board: &Board| board.by_piece(Role::Pawn.of(Color::White));
+        assert_eq!(pawn_islands(white_pawns(&weak)), 2);
+        assert_eq!(pawn_islands(white_pawns(&healthy)), 1);
+    }
+
+    #[test]
+    fn test_same_score_for_black_and_white() {
+        let fens = include_str!("../../assets/fens.txt");
+
+        for line in fens.lines().step_by(10) {
+            let board = parse_board(line);
+            let mut mirrored = board.clone();
+            mirrored.flip_vertical();
+            mirrored.swap_colors();
+
+            // Fresh tables, so the shared key cannot return the other board's entry
+            let key = Zobrist64(0);
+            let (middlegame, endgame) = pawn_score(&board, key, &mut PawnTable::new(8));
+            let mirrored_score = pawn_score(&mirrored, key, &mut PawnTable::new(8));
+            assert_eq!(
+                (middlegame, endgame),
+                (-mirrored_score.0, -mirrored_score.1),
+                "{line}"
+            );
+        }
+    }
+}
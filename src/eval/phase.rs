@@ -1,15 +1,37 @@
-use shakmaty::{ByRole, Chess, Position};
+use shakmaty::{Board, ByRole, Role};
 
-const STARTING_SUM: f32 = 24.0;
-const CONSERVATIVE_FACTOR: f32 = 1.5;
+/// Phase weight of all pieces at the start of the game.
+const STARTING_SUM: u8 = 24;
 
-/// Determines how far the game has progressed from opening to endgame
-pub struct Phase(f32);
+/// Scale of the opening and endgame weights.
+pub const PHASE_SCALE: i32 = 256;
+
+/// Opening weight for each phase weight sum, `(sum / 24)^1.5` scaled to `PHASE_SCALE`, so the
+/// game counts as an endgame a little earlier than the material alone suggests.
+const OPENING_WEIGHTS: [i32; STARTING_SUM as usize + 1] = [
+    0, 2, 6, 11, 17, 24, 32, 40, 49, 59, 69, 79, 91, 102, 114, 126, 139, 153, 166, 180, 195, 210,
+    225, 240, 256,
+];
+
+/// Contribution of a piece to the phase.
+#[inline(always)]
+pub const fn phase_weight(role: Role) -> u8 {
+    match role {
+        Role::Knight | Role::Bishop => 1,
+        Role::Rook => 2,
+        Role::Queen => 4,
+        Role::Pawn | Role::King => 0,
+    }
+}
+
+/// Determines how far the game has progressed from opening to endgame, from the summed phase
+/// weights of the pieces on the board
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Phase(pub u8);
 
 impl Phase {
-    pub fn new(pos: &Chess) -> Phase {
-        let sum: u8 = pos
-            .board()
+    pub fn new(board: &Board) -> Phase {
+        let sum: u8 = board
             .material()
             .iter()
             .map(
@@ -19,38 +41,48 @@ impl Phase {
                      rook,
                      queen,
                      ..
-                 }| { knight + bishop + rook * 2 + queen * 4 },
+                 }| {
+                    knight * phase_weight(Role::Knight)
+                        + bishop * phase_weight(Role::Bishop)
+                        + rook * phase_weight(Role::Rook)
+                        + queen * phase_weight(Role::Queen)
+                },
             )
             .sum();
 
-        // Compute ratio
-        let ratio = sum as f32 / STARTING_SUM;
-
-        // Add conservatism
-        let phase = ratio.powf(CONSERVATIVE_FACTOR);
-
-        Phase(phase)
+        Phase(sum)
     }
 
     /// Outputs close the game is to the opening
-    /// 1.0 = 100% opening
-    /// 0.0 = 100% endgame
-    pub fn opening(&self) -> f32 {
-        self.0
+    /// PHASE_SCALE = 100% opening
+    /// 0 = 100% endgame
+    pub fn opening(&self) -> i32 {
+        // Promotions can raise the sum above its starting value
+        OPENING_WEIGHTS[self.0.min(STARTING_SUM) as usize]
     }
 
     /// Outputs close the game is to the endgame
-    /// 1.0 = 100% endgame
-    /// 0.0 = 100% opening
-    pub fn endgame(&self) -> f32 {
-        1.0 - self.0
+    /// PHASE_SCALE = 100% endgame
+    /// 0 = 100% opening
+    pub fn endgame(&self) -> i32 {
+        PHASE_SCALE - self.opening()
+    }
+
+    /// Blends a middlegame and an endgame score by the phase.
+    pub fn taper(&self, middlegame: i32, endgame: i32) -> i32 {
+        (middlegame * self.opening() + endgame * self.endgame()) / PHASE_SCALE
+    }
+
+    /// Whether only kings and pawns are left on the board
+    pub fn is_pawn_endgame(&self) -> bool {
+        self.0 == 0
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use shakmaty::{fen::Fen, CastlingMode, Chess};
+    use shakmaty::{fen::Fen, CastlingMode, Chess, Position};
     use std::str::FromStr;
 
     fn parse_fen(fen_str: &str) -> Chess {
@@ -63,32 +95,42 @@ mod tests {
     #[test]
     fn test_phase_opening() {
         let pos = Chess::default();
-        let phase = Phase::new(&pos);
-        assert_eq!(phase.opening(), 1.0);
-        assert_eq!(phase.endgame(), 0.0);
+        let phase = Phase::new(pos.board());
+        assert_eq!(phase.opening(), PHASE_SCALE);
+        assert_eq!(phase.endgame(), 0);
     }
 
     #[test]
     fn test_phase_endgame() {
         // Only kings and pawns
         let pos = parse_fen("4k3/8/8/3p4/3P4/8/8/4K3 w - - 0 1");
-        let phase = Phase::new(&pos);
-        assert_eq!(phase.opening(), 0.0);
-        assert_eq!(phase.endgame(), 1.0);
+        let phase = Phase::new(pos.board());
+        assert_eq!(phase.opening(), 0);
+        assert_eq!(phase.endgame(), PHASE_SCALE);
+        assert!(phase.is_pawn_endgame());
     }
 
     #[test]
     fn test_phase_middlegame() {
         // Remove both queens from opening position
         let pos = parse_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1");
-        let phase = Phase::new(&pos);
+        let phase = Phase::new(pos.board());
         assert!(
-            phase.opening() > 0.0 && phase.opening() < 1.0,
-            "Opening must be strictly between 0.0 and 1.0"
+            phase.opening() > 0 && phase.opening() < PHASE_SCALE,
+            "Opening must be strictly between 0 and PHASE_SCALE"
         );
         assert!(
-            phase.endgame() > 0.0 && phase.endgame() < 1.0,
-            "Endgame must be strictly between 0.0 and 1.0"
+            phase.endgame() > 0 && phase.endgame() < PHASE_SCALE,
+            "Endgame must be strictly between 0 and PHASE_SCALE"
         );
+        assert!(!phase.is_pawn_endgame());
+    }
+
+    #[test]
+    fn test_taper() {
+        let phase = Phase(12);
+        assert_eq!(phase.taper(100, 100), 100);
+        assert_eq!(phase.taper(-40, 60), -(phase.taper(40, -60)));
+        assert_eq!(Phase(STARTING_SUM + 4).taper(30, 90), 30);
     }
 }
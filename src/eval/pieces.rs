@@ -0,0 +1,174 @@
+use shakmaty::{attacks, Bitboard, Board, Color, File, Rank, Square};
+
+const BISHOP_PAIR: (i32, i32) = (30, 50);
+// Rooks on files without pawns, or without pawns of their side
+const ROOK_OPEN_FILE: (i32, i32) = (25, 10);
+const ROOK_HALF_OPEN_FILE: (i32, i32) = (12, 5);
+// Pieces on the seventh rank, where they attack pawns or confine the enemy king
+const ROOK_ON_SEVENTH: (i32, i32) = (20, 30);
+const QUEEN_ON_SEVENTH: (i32, i32) = (10, 20);
+// For two rooks on a file with nothing between them
+const DOUBLED_ROOKS: (i32, i32) = (15, 10);
+const TRAPPED_BISHOP: (i32, i32) = (-100, -80);
+const TRAPPED_ROOK: (i32, i32) = (-50, -10);
+
+/// Squares where a bishop of White is cut off by the enemy pawn on the second square once it has
+/// taken a pawn, mirrored for Black.
+const TRAPPED_BISHOP_SQUARES: [(Square, Square); 4] = [
+    (Square::A7, Square::B6),
+    (Square::H7, Square::G6),
+    (Square::B8, Square::C7),
+    (Square::G8, Square::F7),
+];
+
+/// Evaluates piece combinations and placement patterns from White's point of view.
+pub fn piece_terms(board: &Board) -> (i32, i32) {
+    let mut middlegame = 0;
+    let mut endgame = 0;
+    for color in Color::ALL {
+        let sign = color.fold_wb(1, -1);
+        let (color_middlegame, color_endgame) = piece_terms_of(board, color);
+        middlegame += sign * color_middlegame;
+        endgame += sign * color_endgame;
+    }
+    (middlegame, endgame)
+}
+
+/// Evaluates the pieces of one side.
+fn piece_terms_of(board: &Board, color: Color) -> (i32, i32) {
+    let mut middlegame = 0;
+    let mut endgame = 0;
+    let mut add = |(mg, eg): (i32, i32)| {
+        middlegame += mg;
+        endgame += eg;
+    };
+
+    let ours = board.by_color(color);
+    let our_pawns = board.pawns() & ours;
+    let their_pawns = board.pawns() & !ours;
+
+    let bishops = board.bishops() & ours;
+    if (bishops & Bitboard::DARK_SQUARES).any() && (bishops & Bitboard::LIGHT_SQUARES).any() {
+        add(BISHOP_PAIR);
+    }
+
+    // The seventh rank matters while enemy pawns are on it or the enemy king is held behind it
+    let seventh = Bitboard::from_rank(color.relative_rank(Rank::Seventh));
+    let eighth = Bitboard::from_rank(color.relative_rank(Rank::Eighth));
+    let seventh_matters = (their_pawns & seventh).any() || (board.kings() & !ours & eighth).any();
+
+    let rooks = board.rooks() & ours;
+    for rook in rooks {
+        let file = Bitboard::from_file(rook.file());
+        if (board.pawns() & file).is_empty() {
+            add(ROOK_OPEN_FILE);
+        } else if (our_pawns & file).is_empty() {
+            add(ROOK_HALF_OPEN_FILE);
+        }
+
+        if seventh_matters && seventh.contains(rook) {
+            add(ROOK_ON_SEVENTH);
+        }
+
+        // Counted once, from the rook closer to the side's own backrank
+        let doubled = attacks::rook_attacks(rook, board.occupied()) & file & rooks;
+        if doubled
+            .into_iter()
+            .any(|other| color.fold_wb(other > rook, other < rook))
+        {
+            add(DOUBLED_ROOKS);
+        }
+    }
+
+    if seventh_matters && (board.queens() & ours & seventh).any() {
+        add(QUEEN_ON_SEVENTH);
+    }
+
+    for (bishop, pawn) in TRAPPED_BISHOP_SQUARES {
+        let (bishop, pawn) = (relative_square(color, bishop), relative_square(color, pawn));
+        if bishops.contains(bishop) && their_pawns.contains(pawn) {
+            add(TRAPPED_BISHOP);
+        }
+    }
+
+    if let Some(king) = board.king_of(color) {
+        if rooks
+            .into_iter()
+            .any(|rook| is_trapped_rook(color, king, rook))
+        {
+            add(TRAPPED_ROOK);
+        }
+    }
+
+    (middlegame, endgame)
+}
+
+/// Tests whether a rook is shut in the corner by a king that stepped aside without castling.
+fn is_trapped_rook(color: Color, king: Square, rook: Square) -> bool {
+    let backrank = color.backrank();
+    if king.rank() != backrank || rook.rank().distance(backrank) > 1 {
+        return false;
+    }
+
+    match king.file() {
+        File::F | File::G => rook.file() > king.file(),
+        File::B | File::C | File::D => rook.file() < king.file(),
+        _ => false,
+    }
+}
+
+/// The square as seen from the given side, mirroring ranks for Black.
+fn relative_square(color: Color, square: Square) -> Square {
+    Square::from_coords(square.file(), color.relative_rank(square.rank()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, CastlingMode, Chess, Position};
+    use std::str::FromStr;
+
+    fn parse_board(fen_str: &str) -> Board {
+        Fen::from_str(fen_str)
+            .unwrap()
+            .into_position::<Chess>(CastlingMode::Standard)
+            .unwrap()
+            .board()
+            .clone()
+    }
+
+    #[test]
+    fn test_start_position_is_balanced() {
+        assert_eq!(piece_terms(Chess::default().board()), (0, 0));
+    }
+
+    #[test]
+    fn test_bishop_pair() {
+        let board = parse_board("2b1k3/8/8/8/8/8/8/2B1KB2 w - - 0 1");
+        assert_eq!(piece_terms(&board), BISHOP_PAIR);
+    }
+
+    #[test]
+    fn test_rooks() {
+        // Doubled rooks on the open d-file, with the one on the seventh rank facing pawns there
+        let board = parse_board("4k3/p2R4/8/8/8/8/P7/3RK3 w - - 0 1");
+        assert_eq!(
+            piece_terms_of(&board, Color::White),
+            (
+                2 * ROOK_OPEN_FILE.0 + ROOK_ON_SEVENTH.0 + DOUBLED_ROOKS.0,
+                2 * ROOK_OPEN_FILE.1 + ROOK_ON_SEVENTH.1 + DOUBLED_ROOKS.1,
+            )
+        );
+    }
+
+    #[test]
+    fn test_trapped_pieces() {
+        let bishop = parse_board("4k3/B7/1p6/8/8/8/8/4K3 w - - 0 1");
+        assert!(piece_terms_of(&bishop, Color::White).0 <= TRAPPED_BISHOP.0);
+
+        let rook = parse_board("4k3/8/8/8/8/8/6PP/5K1R w - - 0 1");
+        assert!(is_trapped_rook(Color::White, Square::F1, Square::H1));
+        assert!(piece_terms_of(&rook, Color::White).0 < 0);
+        assert!(!is_trapped_rook(Color::Black, Square::E8, Square::H8));
+    }
+}
@@ -1,22 +1,19 @@
-use crate::eval::phase::Phase;
 use shakmaty::{Color, Piece, Role, Square};
 
-/// Calculates the positional score of a piece on a given square for a given phase.
-pub fn position_score(piece: Piece, square: Square, phase: &Phase) -> i32 {
+/// Calculates the middlegame and endgame positional scores of a piece on a given square.
+pub fn position_score(piece: Piece, square: Square) -> (i32, i32) {
     let mut sq = square as usize;
     if piece.color == Color::Black {
         sq = 63 - sq;
     }
-    let (mg, eg) = match piece.role {
+    match piece.role {
         Role::Pawn => (PAWN_TABLE[sq], PAWN_ENDGAME_TABLE[sq]),
         Role::Knight => (KNIGHT_TABLE[sq], KNIGHT_ENDGAME_TABLE[sq]),
         Role::Bishop => (BISHOP_TABLE[sq], BISHOP_ENDGAME_TABLE[sq]),
         Role::Rook => (ROOK_TABLE[sq], ROOK_ENDGAME_TABLE[sq]),
         Role::Queen => (QUEEN_TABLE[sq], QUEEN_ENDGAME_TABLE[sq]),
         Role::King => (KING_TABLE[sq], KING_ENDGAME_TABLE[sq]),
-    };
-
-    ((mg as f32 * phase.opening()) + (eg as f32 * phase.endgame())).round() as i32
+    }
 }
 
 // Pawns
@@ -183,17 +180,14 @@ mod tests {
             .unwrap()
     }
 
-    fn total_score_for_color(board: Board, phase: &Phase, color: Color) -> i32 {
+    fn total_score_for_color(board: Board, color: Color) -> (i32, i32) {
         board
             .iter()
-            .map(|(square, piece)| {
-                if piece.color == color {
-                    position_score(piece, square, phase)
-                } else {
-                    0
-                }
+            .filter(|(_, piece)| piece.color == color)
+            .map(|(square, piece)| position_score(piece, square))
+            .fold((0, 0), |(mg, eg), (piece_mg, piece_eg)| {
+                (mg + piece_mg, eg + piece_eg)
             })
-            .sum()
     }
 
     #[test]
@@ -203,7 +197,6 @@ mod tests {
         for line in fens.lines() {
             println!("Testing {}", line);
             let pos = parse_fen(line);
-            let phase = Phase::new(&pos);
 
             let board = pos.board().clone();
             let board_rotated = {
@@ -217,8 +210,8 @@ mod tests {
 
             if Fen::from_str(&fen).is_ok() {
                 assert_eq!(
-                    total_score_for_color(board.clone(), &phase, Color::White),
-                    total_score_for_color(board_rotated, &phase, Color::Black),
+                    total_score_for_color(board.clone(), Color::White),
+                    total_score_for_color(board_rotated, Color::Black),
                     "Failed on {}",
                     line
                 );
@@ -0,0 +1,162 @@
+use crate::{
+    board::Board,
+    eval::material::{material_score, MaterialSignature},
+};
+use shakmaty::{Bitboard, Color, File, Rank, Role, Square};
+
+/// Scale factor leaving the evaluation unchanged.
+pub const SCALE_NORMAL: i32 = 64;
+
+// Opposite-coloured bishops with only pawns besides, and with other pieces on the board
+const OPPOSITE_BISHOPS: i32 = 18;
+const OPPOSITE_BISHOPS_WITH_PIECES: i32 = 40;
+// Without pawns, being up no more than a minor piece is rarely enough to win
+const MINOR_PIECE_UP: i32 = 4;
+const MINOR_PIECE_UP_WITH_ROOK: i32 = 14;
+
+/// Scales the evaluation toward a draw for endgames the stronger side can rarely or never win,
+/// as a fraction of `SCALE_NORMAL`.
+pub fn scale_factor(pos: &Board, signature: &MaterialSignature, strong: Color) -> i32 {
+    let weak = !strong;
+    if pos.has_insufficient_material(strong) {
+        return 0;
+    }
+
+    let strong_material = signature.non_pawn_material(strong);
+    let weak_material = signature.non_pawn_material(weak);
+    if signature.count(strong, Role::Pawn) == 0 {
+        // Two knights cannot force mate against a bare king
+        let only_knights =
+            strong_material == signature.count(strong, Role::Knight) * material_score(Role::Knight);
+        if only_knights && weak_material == 0 && signature.count(weak, Role::Pawn) == 0 {
+            return 0;
+        }
+
+        if strong_material - weak_material <= material_score(Role::Bishop) {
+            return if strong_material < material_score(Role::Rook) {
+                0
+            } else if weak_material <= material_score(Role::Bishop) {
+                MINOR_PIECE_UP
+            } else {
+                MINOR_PIECE_UP_WITH_ROOK
+            };
+        }
+    }
+
+    if is_wrong_bishop(pos, signature, strong) {
+        return 0;
+    }
+
+    if has_opposite_bishops(pos, signature) {
+        let only_bishops = strong_material == material_score(Role::Bishop)
+            && weak_material == material_score(Role::Bishop);
+        return if only_bishops {
+            OPPOSITE_BISHOPS
+        } else {
+            OPPOSITE_BISHOPS_WITH_PIECES
+        };
+    }
+
+    SCALE_NORMAL
+}
+
+/// Tests whether each side has a single bishop, on squares of different colours.
+fn has_opposite_bishops(pos: &Board, signature: &MaterialSignature) -> bool {
+    let bishops = pos.board().bishops();
+    signature.count(Color::White, Role::Bishop) == 1
+        && signature.count(Color::Black, Role::Bishop) == 1
+        && (bishops & Bitboard::DARK_SQUARES).count() == 1
+}
+
+/// Tests whether the stronger side has only rook pawns and bishops not controlling the
+/// promotion square, with the defending king in front of the pawns, which is a known draw.
+fn is_wrong_bishop(pos: &Board, signature: &MaterialSignature, strong: Color) -> bool {
+    let board = pos.board();
+    let bishops = signature.count(strong, Role::Bishop);
+    if bishops == 0 || signature.non_pawn_material(strong) != bishops * material_score(Role::Bishop)
+    {
+        return false;
+    }
+
+    let pawns = board.by_piece(Role::Pawn.of(strong));
+    let file = if (pawns & !Bitboard::from_file(File::A)).is_empty() {
+        File::A
+    } else if (pawns & !Bitboard::from_file(File::H)).is_empty() {
+        File::H
+    } else {
+        return false;
+    };
+
+    let promotion = Square::from_coords(file, strong.relative_rank(Rank::Eighth));
+    let promotion_color = if promotion.is_dark() {
+        Bitboard::DARK_SQUARES
+    } else {
+        Bitboard::LIGHT_SQUARES
+    };
+    let bishops_control = (board.by_piece(Role::Bishop.of(strong)) & promotion_color).any();
+
+    pawns.any()
+        && !bishops_control
+        && board
+            .king_of(!strong)
+            .is_some_and(|king| king.distance(promotion) <= 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, CastlingMode, Chess};
+    use std::str::FromStr;
+
+    fn scale_of(fen_str: &str, strong: Color) -> i32 {
+        let pos: Chess = Fen::from_str(fen_str)
+            .unwrap()
+            .into_position(CastlingMode::Standard)
+            .unwrap();
+        let board = Board::from(&pos);
+        scale_factor(&board, &MaterialSignature::new(board.board()), strong)
+    }
+
+    #[test]
+    fn test_start_position_is_not_scaled() {
+        let board = Board::default();
+        let signature = MaterialSignature::new(board.board());
+        assert_eq!(scale_factor(&board, &signature, Color::White), SCALE_NORMAL);
+    }
+
+    #[test]
+    fn test_drawn_minor_piece_endings() {
+        assert_eq!(scale_of("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1", Color::White), 0);
+        assert_eq!(
+            scale_of("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1", Color::White),
+            0
+        );
+        assert_eq!(
+            scale_of("4k3/8/8/8/8/8/8/3RK2b w - - 0 1", Color::White),
+            MINOR_PIECE_UP
+        );
+    }
+
+    #[test]
+    fn test_opposite_bishops() {
+        assert_eq!(
+            scale_of("4k3/5p2/2b5/8/8/4BP2/5P2/4K3 w - - 0 1", Color::White),
+            OPPOSITE_BISHOPS
+        );
+        // Same-coloured bishops are not scaled
+        assert_eq!(
+            scale_of("4k3/5p2/3b4/8/8/4BP2/5P2/4K3 w - - 0 1", Color::White),
+            SCALE_NORMAL
+        );
+    }
+
+    #[test]
+    fn test_wrong_bishop() {
+        // The light-squared bishop cannot drive the king from the dark h8 corner
+        assert_eq!(scale_of("7k/8/8/7P/8/8/4B3/4K3 w - - 0 1", Color::White), 0);
+        assert_eq!(
+            scale_of("7k/8/8/7P/8/8/3B4/4K3 w - - 0 1", Color::White),
+            SCALE_NORMAL
+        );
+    }
+}
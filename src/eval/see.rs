@@ -0,0 +1,169 @@
+use crate::eval::material::material_score;
+use shakmaty::{Bitboard, Board, Color, Move, Rank, Role, Square};
+
+/// Value of a king in exchanges, large enough that losing it outweighs any material.
+const KING_VALUE: i32 = 20000;
+
+/// Exchange value of a piece.
+#[inline(always)]
+fn value(role: Role) -> i32 {
+    match role {
+        Role::King => KING_VALUE,
+        role => material_score(role),
+    }
+}
+
+/// Static exchange evaluation of a move.
+/// Resolves the sequence of captures on the target square, where each side recaptures with its
+/// least valuable attacker and may stop when continuing would lose material.
+/// Returns the material gained by the moving side (negative if the exchange loses material).
+pub fn see(board: &Board, mv: Move) -> i32 {
+    let (from, to, role, promotion, captured) = match mv {
+        Move::Normal {
+            role,
+            from,
+            capture,
+            to,
+            promotion,
+        } => (from, to, role, promotion, capture.map_or(0, value)),
+        Move::EnPassant { from, to } => (from, to, Role::Pawn, None, value(Role::Pawn)),
+        // Castling never captures and leaves the king and rook on safe squares
+        _ => return 0,
+    };
+
+    let Some(mut side) = board.color_at(from) else {
+        return 0;
+    };
+
+    // Remove the moving piece (and a pawn captured en passant) from the board
+    let mut occupied = board.occupied().without(from);
+    if let Move::EnPassant { .. } = mv {
+        occupied.discard(Square::from_coords(to.file(), from.rank()));
+    }
+
+    // Material balance after each capture in the sequence, from the capturing side's perspective
+    let mut gains = [0; 32];
+    gains[0] = captured + promotion.map_or(0, |promoted| value(promoted) - value(Role::Pawn));
+
+    // The piece that would be captured next
+    let mut on_square = value(promotion.unwrap_or(role));
+    let mut depth = 0;
+
+    loop {
+        side = !side;
+
+        // Sliders behind pieces that have captured are revealed as occupancy shrinks
+        let attackers = attackers_to(board, to, occupied);
+        let Some((square, attacker)) =
+            least_valuable_attacker(board, attackers & board.by_color(side))
+        else {
+            break;
+        };
+
+        // The king can only capture onto an undefended square
+        if attacker == Role::King && (attackers & board.by_color(!side)).any() {
+            break;
+        }
+
+        depth += 1;
+        gains[depth] = on_square - gains[depth - 1];
+        on_square = value(attacker);
+
+        // Pawns recapturing onto the last rank promote
+        if attacker == Role::Pawn && is_back_rank(to) {
+            gains[depth] += value(Role::Queen) - value(Role::Pawn);
+            on_square = value(Role::Queen);
+        }
+
+        occupied.discard(square);
+
+        if depth == gains.len() - 1 {
+            break;
+        }
+    }
+
+    // Let either side stop the sequence when continuing does not pay off
+    while depth > 0 {
+        gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+        depth -= 1;
+    }
+
+    gains[0]
+}
+
+/// All pieces of both colors attacking a square, given the occupancy.
+#[inline(always)]
+fn attackers_to(board: &Board, square: Square, occupied: Bitboard) -> Bitboard {
+    (board.attacks_to(square, Color::White, occupied)
+        | board.attacks_to(square, Color::Black, occupied))
+        & occupied
+}
+
+/// Finds the attacker with the lowest value among the given attackers.
+#[inline(always)]
+fn least_valuable_attacker(board: &Board, attackers: Bitboard) -> Option<(Square, Role)> {
+    Role::ALL.into_iter().find_map(|role| {
+        (attackers & board.by_role(role))
+            .first()
+            .map(|square| (square, role))
+    })
+}
+
+#[inline(always)]
+fn is_back_rank(square: Square) -> bool {
+    matches!(square.rank(), Rank::First | Rank::Eighth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, uci::UciMove, CastlingMode, Chess, Position};
+    use std::str::FromStr;
+
+    fn see_of(fen_str: &str, uci: &str) -> i32 {
+        let pos: Chess = Fen::from_str(fen_str)
+            .unwrap()
+            .into_position(CastlingMode::Standard)
+            .unwrap();
+        let mv = UciMove::from_ascii(uci.as_bytes())
+            .unwrap()
+            .to_move(&pos)
+            .unwrap();
+        see(pos.board(), mv)
+    }
+
+    #[test]
+    fn test_undefended_capture() {
+        assert_eq!(see_of("4k3/8/8/4p3/8/8/8/4RK2 w - - 0 1", "e1e5"), 100);
+    }
+
+    #[test]
+    fn test_defended_capture_loses() {
+        assert_eq!(see_of("4k3/3p4/4p3/8/8/8/8/4QK2 w - - 0 1", "e1e6"), -800);
+    }
+
+    #[test]
+    fn test_xray_attacker() {
+        // The second white rook only attacks e5 once the first one has captured
+        assert_eq!(see_of("4r1k1/8/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"), 100);
+        assert_eq!(see_of("4r1k1/8/8/4p3/8/8/8/4R1K1 w - - 0 1", "e1e5"), -400);
+    }
+
+    #[test]
+    fn test_capturing_side_may_stop() {
+        // Bxd5 exd5 trades a bishop for a knight, recapturing with the queen would lose it
+        assert_eq!(see_of("3rk3/8/4p3/3n4/8/1B6/8/3QK3 w - - 0 1", "b3d5"), -10);
+    }
+
+    #[test]
+    fn test_king_cannot_capture_defended_piece() {
+        // The rook behind the queen keeps d7 defended
+        assert_eq!(see_of("4k3/3p4/8/8/3Q4/8/8/3RK3 w - - 0 1", "d4d7"), 100);
+        assert_eq!(see_of("4k3/3p4/8/8/3Q4/8/8/4K3 w - - 0 1", "d4d7"), -800);
+    }
+
+    #[test]
+    fn test_quiet_move_to_attacked_square() {
+        assert_eq!(see_of("4k3/2p5/8/8/4N3/8/8/4K3 w - - 0 1", "e4d6"), -320);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::eval::attacks::AttackMap;
+use shakmaty::{attacks, Bitboard, Board, Color, Role};
+
+/// Bonus for every enemy piece attacked by a piece of lower value, by the role of the attacked
+/// piece.
+const THREAT_BY_LESSER: [(i32, i32); 6] = [(0, 0), (30, 25), (30, 25), (45, 40), (50, 45), (0, 0)];
+
+// For every enemy piece we attack that is not defended at all, or that we attack twice while it
+// is defended only once
+const HANGING: (i32, i32) = (35, 20);
+// For every enemy piece one of our pawns would attack after a safe push
+const PAWN_PUSH_THREAT: (i32, i32) = (15, 12);
+
+/// Bonus for being able to check the enemy king from a square it does not guard, by the role of
+/// the checking piece.
+const SAFE_CHECK: [(i32, i32); 6] = [(0, 0), (30, 5), (20, 5), (35, 5), (30, 5), (0, 0)];
+
+/// Evaluates the threats of both sides from White's point of view.
+pub fn threats(board: &Board, attacks: &AttackMap) -> (i32, i32) {
+    let mut middlegame = 0;
+    let mut endgame = 0;
+    for color in Color::ALL {
+        let sign = color.fold_wb(1, -1);
+        let (color_middlegame, color_endgame) = threats_of(board, attacks, color);
+        middlegame += sign * color_middlegame;
+        endgame += sign * color_endgame;
+    }
+    (middlegame, endgame)
+}
+
+/// Evaluates the threats one side makes against the other.
+fn threats_of(board: &Board, attack_map: &AttackMap, color: Color) -> (i32, i32) {
+    let mut middlegame = 0;
+    let mut endgame = 0;
+    let mut add = |(mg, eg): (i32, i32), count: usize| {
+        middlegame += mg * count as i32;
+        endgame += eg * count as i32;
+    };
+
+    let theirs = board.by_color(!color);
+    let their_pieces = theirs & !board.pawns() & !board.kings();
+    let ours = attack_map.all(color);
+    let defended = attack_map.all(!color);
+
+    // Knights and bishops count as equal, so only pawns threaten them
+    let pawns = attack_map.by_role(color, Role::Pawn);
+    let minors = attack_map.by_role(color, Role::Knight) | attack_map.by_role(color, Role::Bishop);
+    let rooks = attack_map.by_role(color, Role::Rook);
+    for (victim, lesser) in [
+        (Role::Knight, pawns),
+        (Role::Bishop, pawns),
+        (Role::Rook, pawns | minors),
+        (Role::Queen, pawns | minors | rooks),
+    ] {
+        let attacked = board.by_piece(victim.of(!color)) & lesser;
+        add(THREAT_BY_LESSER[victim as usize - 1], attacked.count());
+    }
+
+    let outnumbered = attack_map.double(color) & !attack_map.double(!color);
+    add(
+        HANGING,
+        (their_pieces & (ours & !defended | outnumbered)).count(),
+    );
+
+    // Pawns pushed to squares the enemy does not control, or that we control as well
+    let our_pawns = board.by_piece(Role::Pawn.of(color));
+    let safe = !defended | ours;
+    let pushed = our_pawns
+        .into_iter()
+        .filter_map(|pawn| pawn.offset(color.fold_wb(8, -8)))
+        .filter(|&to| !board.occupied().contains(to) && safe.contains(to))
+        .fold(Bitboard::EMPTY, |pushed, to| {
+            pushed | attacks::pawn_attacks(color, to)
+        });
+    add(PAWN_PUSH_THREAT, (pushed & their_pieces).count());
+
+    if let Some(king) = board.king_of(!color) {
+        let occupied = board.occupied();
+        let unguarded = !defended & !board.by_color(color);
+        for role in [Role::Knight, Role::Bishop, Role::Rook, Role::Queen] {
+            let checks = attacks::attacks(king, role.of(!color), occupied);
+            if (checks & attack_map.by_role(color, role) & unguarded).any() {
+                add(SAFE_CHECK[role as usize - 1], 1);
+            }
+        }
+    }
+
+    (middlegame, endgame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, CastlingMode, Chess, Position};
+    use std::str::FromStr;
+
+    fn parse_board(fen_str: &str) -> Board {
+        Fen::from_str(fen_str)
+            .unwrap()
+            .into_position::<Chess>(CastlingMode::Standard)
+            .unwrap()
+            .board()
+            .clone()
+    }
+
+    fn threats_of_white(fen_str: &str) -> (i32, i32) {
+        let board = parse_board(fen_str);
+        threats_of(&board, &AttackMap::new(&board), Color::White)
+    }
+
+    #[test]
+    fn test_start_position_is_balanced() {
+        let board = Chess::default().board().clone();
+        assert_eq!(threats(&board, &AttackMap::new(&board)), (0, 0));
+    }
+
+    #[test]
+    fn test_pawn_attacks_knight() {
+        // The knight is defended, so only the pawn's threat counts
+        assert_eq!(
+            threats_of_white("4k3/8/3p4/4n3/3P4/8/8/4K3 w - - 0 1"),
+            THREAT_BY_LESSER[Role::Knight as usize - 1]
+        );
+    }
+
+    #[test]
+    fn test_hanging_piece() {
+        // The bishop attacks the undefended knight, which is worth as much as the bishop
+        assert_eq!(threats_of_white("7k/8/8/8/8/7n/8/4KB2 w - - 0 1"), HANGING);
+    }
+
+    #[test]
+    fn test_piece_attacked_twice_defended_once() {
+        // Both knights attack the knight on e5, which only the d6 pawn defends
+        assert_eq!(
+            threats_of_white("7k/8/3p4/4n3/2N5/5N2/8/4K3 w - - 0 1"),
+            HANGING
+        );
+        // A second defender holds it
+        assert_eq!(
+            threats_of_white("4r2k/8/3p4/4n3/2N5/5N2/8/4K3 w - - 0 1"),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn test_safe_pawn_push_threat() {
+        // Pushing d3-d4 attacks the knight on e5
+        assert_eq!(
+            threats_of_white("4k3/8/8/4n3/8/3P4/8/4K3 w - - 0 1"),
+            PAWN_PUSH_THREAT
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod board;
 pub mod bot;
 pub mod eval;
 pub mod search;
@@ -8,6 +9,8 @@ pub enum SearchControl {
     ToDepth(u8),
     // Search for a approximate duration (in milliseconds)
     TimeLimit(u64),
+    // Prove a forced mate in at most the given number of moves
+    Mate(u8),
 }
 
 /// Instructions for the search thread
@@ -19,15 +22,23 @@ pub enum SearchCommand {
     Stop,
     Quit,
     Reset,
+    // Evaluate positions with another evaluator from now on
+    SetEvaluator(eval::evaluator::EvaluatorKind),
 }
 
 /// Search information to be logged
 pub enum SearchInfo {
     BestMove(shakmaty::Move),
+    // Free-form message for the user interface
+    String(String),
     Info {
         depth: u8,
+        // Deepest ply reached, including quiescence search
+        seldepth: u8,
         pv: Vec<shakmaty::Move>,
         score: i32,
         nodes: u64,
+        // Whether the score is exact or only a bound from a failed aspiration window
+        bound: search::transposition::Bound,
     },
 }
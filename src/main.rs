@@ -1,9 +1,19 @@
 use crossbeam_channel::unbounded;
 use skakarlak::bot::{controller::Controller, input::InputListener};
-use skakarlak::search::Searcher;
-use std::thread;
+use skakarlak::eval::evaluator::EvaluatorKind;
+use skakarlak::search::{params::SearchParams, Searcher};
+use std::{env, process, thread};
 
 fn main() {
+    // Choose the evaluator with `--eval <name>`, which can also be changed by UCI option later
+    let evaluator = match evaluator_arg(env::args().skip(1)) {
+        Ok(evaluator) => evaluator,
+        Err(error) => {
+            eprintln!("{error}");
+            process::exit(2);
+        }
+    };
+
     // Initialize channels
     let (input_tx, input_rx) = unbounded();
     let (cmd_tx, cmd_rx) = unbounded();
@@ -13,8 +23,27 @@ fn main() {
     thread::spawn(|| InputListener::new(input_tx).run());
 
     // Spawn search thread
-    thread::spawn(|| Searcher::new(cmd_rx, info_tx).run());
+    thread::spawn(move || {
+        Searcher::with_evaluator_kind(cmd_rx, info_tx, SearchParams::default(), evaluator).run()
+    });
 
     // Run controller on main thread
-    Controller::new(input_rx, cmd_tx, info_rx, "engine.log").run();
+    Controller::with_evaluator(input_rx, cmd_tx, info_rx, "engine.log", evaluator).run();
+}
+
+/// Reads the evaluator from the command line arguments.
+fn evaluator_arg(mut args: impl Iterator<Item = String>) -> Result<EvaluatorKind, String> {
+    let mut evaluator = EvaluatorKind::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--eval" => {
+                evaluator = args
+                    .next()
+                    .ok_or("missing evaluator after --eval")?
+                    .parse()?;
+            }
+            _ => return Err(format!("unknown argument '{arg}'")),
+        }
+    }
+    Ok(evaluator)
 }
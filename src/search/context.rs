@@ -0,0 +1,115 @@
+use crate::{
+    board::Board,
+    eval::evaluator::{Evaluator, StandardEvaluator},
+    search::{
+        heuristics::Heuristics, params::SearchParams, pv::PvTable,
+        transposition::FastTranspositionTable,
+    },
+};
+use shakmaty::Move;
+
+/// Maximum number of plies the search stack can hold.
+pub const MAX_PLY: usize = 128;
+
+/// Depths and move indices beyond this share the last entry of the reduction table.
+const LMR_TABLE_SIZE: usize = 64;
+
+/// Search state belonging to a single ply of the current line.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StackEntry {
+    // The move being searched from this ply, if it is not a null move
+    pub current_move: Option<Move>,
+    // The move being searched from this ply is a null move
+    pub null_move: bool,
+    // Move skipped at this ply while testing the TT move for singularity
+    pub excluded_move: Option<Move>,
+    // Plies the line leading to this ply has been extended by
+    pub extensions: u8,
+}
+
+/// State shared by all nodes of a search.
+pub struct SearchContext<E: Evaluator = StandardEvaluator> {
+    pub tt: FastTranspositionTable,
+    pub evaluator: E,
+    pub params: SearchParams,
+    pub heuristics: Heuristics,
+    pub pv: PvTable,
+    pub nodes: u64,
+    // Deepest ply reached, including quiescence search
+    pub seldepth: u8,
+    pub stack: [StackEntry; MAX_PLY + 1],
+    // Null moves are not tried before this ply (used by verification searches)
+    pub null_move_min_ply: u8,
+    // Late move reductions by remaining depth and move index
+    lmr_table: [[u8; LMR_TABLE_SIZE]; LMR_TABLE_SIZE],
+}
+
+impl SearchContext {
+    pub fn new(tt: FastTranspositionTable, params: SearchParams) -> Self {
+        Self::with_evaluator(tt, params, StandardEvaluator::default())
+    }
+}
+
+impl<E: Evaluator> SearchContext<E> {
+    pub fn with_evaluator(tt: FastTranspositionTable, params: SearchParams, evaluator: E) -> Self {
+        SearchContext {
+            tt,
+            evaluator,
+            lmr_table: lmr_table(&params),
+            params,
+            heuristics: Heuristics::default(),
+            pv: PvTable::default(),
+            nodes: 0,
+            seldepth: 0,
+            stack: [StackEntry::default(); MAX_PLY + 1],
+            null_move_min_ply: 0,
+        }
+    }
+
+    /// Number of plies to reduce the search of a late move by.
+    pub fn reduction(&self, depth: u8, move_index: usize) -> u8 {
+        let depth = (depth as usize).min(LMR_TABLE_SIZE - 1);
+        self.lmr_table[depth][move_index.min(LMR_TABLE_SIZE - 1)]
+    }
+
+    /// Number of quiet moves searched at shallow depth before the rest are pruned.
+    pub fn late_move_count(&self, depth: u8) -> usize {
+        self.params.lmp_base + depth as usize * depth as usize
+    }
+
+    /// Plays a move on the board, letting the evaluator follow it.
+    pub fn play(&mut self, board: &mut Board, mv: Move) {
+        self.evaluator.play(board, Some(mv));
+        board.play(mv);
+    }
+
+    /// Passes the turn on the board, letting the evaluator follow it.
+    pub fn play_null(&mut self, board: &mut Board) {
+        self.evaluator.play(board, None);
+        board.play_null();
+    }
+
+    /// Undoes the last move on the board, letting the evaluator follow it.
+    pub fn undo(&mut self, board: &mut Board) {
+        board.undo();
+        self.evaluator.undo(board);
+    }
+
+    /// Prepares the context for a new search from the root.
+    pub fn reset_stack(&mut self) {
+        self.stack = [StackEntry::default(); MAX_PLY + 1];
+        self.null_move_min_ply = 0;
+    }
+}
+
+/// Precomputes the logarithmic late move reduction formula.
+fn lmr_table(params: &SearchParams) -> [[u8; LMR_TABLE_SIZE]; LMR_TABLE_SIZE] {
+    let mut table = [[0; LMR_TABLE_SIZE]; LMR_TABLE_SIZE];
+    for (depth, row) in table.iter_mut().enumerate().skip(1) {
+        for (move_index, reduction) in row.iter_mut().enumerate().skip(1) {
+            let log_product = (depth as f32).ln() * (move_index as f32).ln();
+            *reduction = (params.lmr_base + log_product / params.lmr_divisor).max(0.0) as u8;
+        }
+    }
+    table
+}
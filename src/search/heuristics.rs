@@ -0,0 +1,190 @@
+use crate::search::context::MAX_PLY;
+use shakmaty::{Color, Move};
+
+/// History scores saturate towards this magnitude.
+const MAX_HISTORY: i32 = 16384;
+
+/// Largest history adjustment made by a single cutoff.
+const MAX_HISTORY_BONUS: i32 = 1200;
+
+/// Ordering scores of quiet moves, relative to other quiet moves.
+const KILLER_SCORES: [i32; 2] = [900, 850];
+const COUNTERMOVE_SCORE: i32 = 800;
+const HISTORY_SCALE: i32 = 24;
+
+/// Statistics on which quiet moves caused beta cutoffs, used to order quiet moves.
+pub struct Heuristics {
+    // Two most recent quiet cutoff moves for each ply
+    killers: [[Option<Move>; 2]; MAX_PLY + 1],
+    // Butterfly history by side to move, origin square and target square
+    history: [[[i32; 64]; 64]; 2],
+    // Quiet cutoff move replying to the previous move, by side to move and the previous move's
+    // origin and target squares
+    countermoves: [[[Option<Move>; 64]; 64]; 2],
+}
+
+impl Default for Heuristics {
+    fn default() -> Self {
+        Heuristics {
+            killers: [[None; 2]; MAX_PLY + 1],
+            history: [[[0; 64]; 64]; 2],
+            countermoves: [[[None; 64]; 64]; 2],
+        }
+    }
+}
+
+impl Heuristics {
+    /// Scores a quiet move for ordering. Higher is more promising.
+    pub fn quiet_score(&self, turn: Color, ply: u8, previous: Option<Move>, mv: &Move) -> i32 {
+        let killers = &self.killers[ply as usize];
+        if let Some(i) = killers
+            .iter()
+            .position(|killer| killer.as_ref() == Some(mv))
+        {
+            return KILLER_SCORES[i];
+        }
+
+        if previous.is_some_and(|previous| self.countermove(turn, previous) == Some(*mv)) {
+            return COUNTERMOVE_SCORE;
+        }
+
+        self.history(turn, mv) / HISTORY_SCALE
+    }
+
+    /// Most recent quiet moves causing a cutoff at the given ply, newest first.
+    pub fn killers(&self, ply: u8) -> [Option<Move>; 2] {
+        self.killers[ply as usize]
+    }
+
+    /// Records a quiet move causing a beta cutoff.
+    /// The quiet moves searched before it without causing a cutoff are penalized.
+    pub fn update(
+        &mut self,
+        turn: Color,
+        ply: u8,
+        depth: u8,
+        previous: Option<Move>,
+        cutoff_move: Move,
+        failed_quiets: &[Move],
+    ) {
+        // Keep the two most recent distinct killers
+        let killers = &mut self.killers[ply as usize];
+        if killers[0] != Some(cutoff_move) {
+            killers[1] = killers[0];
+            killers[0] = Some(cutoff_move);
+        }
+
+        if let Some(previous) = previous {
+            let (from, to) = squares(&previous);
+            self.countermoves[turn as usize][from][to] = Some(cutoff_move);
+        }
+
+        let bonus = (depth as i32 * depth as i32 * 16).min(MAX_HISTORY_BONUS);
+        self.add_history(turn, &cutoff_move, bonus);
+        for mv in failed_quiets {
+            self.add_history(turn, mv, -bonus);
+        }
+    }
+
+    /// Weakens statistics from previous searches so recent results dominate.
+    pub fn age(&mut self) {
+        self.killers = [[None; 2]; MAX_PLY + 1];
+        for entry in self.history.iter_mut().flatten().flatten() {
+            *entry /= 2;
+        }
+    }
+
+    fn history(&self, turn: Color, mv: &Move) -> i32 {
+        let (from, to) = squares(mv);
+        self.history[turn as usize][from][to]
+    }
+
+    fn countermove(&self, turn: Color, previous: Move) -> Option<Move> {
+        let (from, to) = squares(&previous);
+        self.countermoves[turn as usize][from][to]
+    }
+
+    /// Adjusts a history score with gravity, so scores approach but never exceed the maximum.
+    fn add_history(&mut self, turn: Color, mv: &Move, bonus: i32) {
+        let (from, to) = squares(mv);
+        let entry = &mut self.history[turn as usize][from][to];
+        *entry += bonus - *entry * bonus.abs() / MAX_HISTORY;
+    }
+}
+
+/// Origin and target squares of a move as table indices.
+#[inline(always)]
+fn squares(mv: &Move) -> (usize, usize) {
+    (mv.from().unwrap_or(mv.to()) as usize, mv.to() as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{Role, Square};
+
+    fn quiet(from: Square, to: Square) -> Move {
+        Move::Normal {
+            role: Role::Knight,
+            from,
+            capture: None,
+            to,
+            promotion: None,
+        }
+    }
+
+    #[test]
+    fn test_killers_keep_two_most_recent() {
+        let mut heuristics = Heuristics::default();
+        let (a, b, c) = (
+            quiet(Square::B1, Square::C3),
+            quiet(Square::G1, Square::F3),
+            quiet(Square::B1, Square::A3),
+        );
+
+        for mv in [a, b, c] {
+            heuristics.update(Color::White, 3, 4, None, mv, &[]);
+        }
+
+        assert_eq!(
+            heuristics.quiet_score(Color::White, 3, None, &c),
+            KILLER_SCORES[0]
+        );
+        assert_eq!(
+            heuristics.quiet_score(Color::White, 3, None, &b),
+            KILLER_SCORES[1]
+        );
+        assert!(heuristics.quiet_score(Color::White, 3, None, &a) < COUNTERMOVE_SCORE);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut heuristics = Heuristics::default();
+        let good = quiet(Square::B1, Square::C3);
+        let bad = quiet(Square::G1, Square::H3);
+
+        for _ in 0..1000 {
+            heuristics.update(Color::White, 1, 20, None, good, &[bad]);
+        }
+
+        assert!(heuristics.history(Color::White, &good) <= MAX_HISTORY);
+        assert!(heuristics.history(Color::White, &bad) >= -MAX_HISTORY);
+        assert!(heuristics.history(Color::White, &good) > 0);
+        assert_eq!(heuristics.history(Color::Black, &good), 0);
+    }
+
+    #[test]
+    fn test_countermove() {
+        let mut heuristics = Heuristics::default();
+        let previous = quiet(Square::B8, Square::C6);
+        let reply = quiet(Square::G1, Square::F3);
+
+        heuristics.update(Color::White, 2, 4, Some(previous), reply, &[]);
+        heuristics.age();
+
+        assert_eq!(
+            heuristics.quiet_score(Color::White, 2, Some(previous), &reply),
+            COUNTERMOVE_SCORE
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use shakmaty::{Chess, Move, Position};
+
+/// Proof and disproof numbers of solved nodes.
+const INFINITY: u32 = u32::MAX;
+
+/// Node expansions between checks for interruption.
+const INTERRUPT_INTERVAL: u64 = 1024;
+
+/// Outcome of a mate search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MateResult {
+    // Forced mate within the move limit
+    Mate(Solution),
+    // No forced mate exists within the move limit
+    NoMate,
+    // The node limit was reached or the search was interrupted before either was proven
+    Unknown,
+}
+
+/// A mating move with the continuation against every defence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solution {
+    pub mv: Move,
+    // Every legal reply of the defender and how it is mated (empty if `mv` mates)
+    pub defences: Vec<(Move, Solution)>,
+}
+
+impl Solution {
+    /// Number of moves by the mating side against the most stubborn defence.
+    pub fn moves(&self) -> u8 {
+        1 + self
+            .defences
+            .iter()
+            .map(|(_, solution)| solution.moves())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The line following the most stubborn defence at every move.
+    pub fn main_line(&self) -> Vec<Move> {
+        let mut line = vec![self.mv];
+        if let Some((defence, solution)) = self
+            .defences
+            .iter()
+            .max_by_key(|(_, solution)| solution.moves())
+        {
+            line.push(*defence);
+            line.extend(solution.main_line());
+        }
+        line
+    }
+}
+
+/// Node of the proof tree. The children of a node are stored next to each other.
+struct Node {
+    // Move leading to this node (none at the root)
+    mv: Option<Move>,
+    parent: u32,
+    first_child: u32,
+    child_count: u32,
+    // Moves the mating side has left to deliver mate
+    remaining: u8,
+    // The mating side is to move, so one proven child proves the node
+    attacker_to_move: bool,
+    // Lower bound on the leaves that must be proven to prove the node
+    proof: u32,
+    // Lower bound on the leaves that must be disproven to disprove the node
+    disproof: u32,
+}
+
+/// Searches for a forced mate by the side to move in at most the given number of moves, using
+/// proof-number search. The tree is bounded by `max_nodes`, and `interrupted` is polled
+/// regularly to abandon the search early.
+/// Returns the result along with the number of tree nodes created.
+pub fn solve(
+    position: &Chess,
+    moves: u8,
+    max_nodes: usize,
+    mut interrupted: impl FnMut() -> bool,
+) -> (MateResult, u64) {
+    // Mating takes at least one move
+    if moves == 0 {
+        return (MateResult::NoMate, 0);
+    }
+
+    let (proof, disproof) = leaf_numbers(position, true, moves);
+    let mut tree = vec![Node {
+        mv: None,
+        parent: 0,
+        first_child: 0,
+        child_count: 0,
+        remaining: moves,
+        attacker_to_move: true,
+        proof,
+        disproof,
+    }];
+
+    let mut expansions = 0;
+    while tree[0].proof != 0 && tree[0].disproof != 0 {
+        if tree.len() >= max_nodes || (expansions % INTERRUPT_INTERVAL == 0 && interrupted()) {
+            return (MateResult::Unknown, tree.len() as u64);
+        }
+
+        let (leaf, leaf_position) = most_proving(&tree, position);
+        expand(&mut tree, leaf, &leaf_position);
+        update_ancestors(&mut tree, leaf);
+        expansions += 1;
+    }
+
+    let result = if tree[0].proof == 0 {
+        MateResult::Mate(solution(&tree, 0))
+    } else {
+        MateResult::NoMate
+    };
+    (result, tree.len() as u64)
+}
+
+/// Initial proof and disproof numbers of a new node, or its final ones if it is decided already.
+/// Unsolved nodes are estimated by mobility, since few replies are quicker to refute.
+fn leaf_numbers(position: &Chess, attacker_to_move: bool, remaining: u8) -> (u32, u32) {
+    let moves = position.legal_moves().len() as u32;
+    let attacker = if attacker_to_move {
+        position.turn()
+    } else {
+        !position.turn()
+    };
+
+    if moves == 0 {
+        // Checkmate of the defender proves the node, anything else ends the game without mate
+        if !attacker_to_move && position.is_check() {
+            (0, INFINITY)
+        } else {
+            (INFINITY, 0)
+        }
+    } else if (!attacker_to_move && remaining == 0) || position.has_insufficient_material(attacker)
+    {
+        (INFINITY, 0)
+    } else if attacker_to_move {
+        (1, moves)
+    } else {
+        (moves, 1)
+    }
+}
+
+/// Walks from the root to the leaf whose proof or disproof contributes most to solving the root.
+fn most_proving(tree: &[Node], root: &Chess) -> (usize, Chess) {
+    let mut index = 0;
+    let mut position = root.clone();
+
+    while tree[index].child_count > 0 {
+        let node = &tree[index];
+        let children = node.first_child as usize..(node.first_child + node.child_count) as usize;
+        index = if node.attacker_to_move {
+            children.min_by_key(|&child| tree[child].proof)
+        } else {
+            children.min_by_key(|&child| tree[child].disproof)
+        }
+        .unwrap();
+
+        position.play_unchecked(tree[index].mv.unwrap());
+    }
+
+    (index, position)
+}
+
+/// Adds a child for every legal move of a leaf.
+fn expand(tree: &mut Vec<Node>, index: usize, position: &Chess) {
+    let parent = &tree[index];
+    let attacker_to_move = !parent.attacker_to_move;
+    let remaining = if parent.attacker_to_move {
+        parent.remaining.saturating_sub(1)
+    } else {
+        parent.remaining
+    };
+
+    let first_child = tree.len() as u32;
+    let moves = position.legal_moves();
+    for mv in &moves {
+        let mut child_position = position.clone();
+        child_position.play_unchecked(*mv);
+        let (proof, disproof) = leaf_numbers(&child_position, attacker_to_move, remaining);
+
+        tree.push(Node {
+            mv: Some(*mv),
+            parent: index as u32,
+            first_child: 0,
+            child_count: 0,
+            remaining,
+            attacker_to_move,
+            proof,
+            disproof,
+        });
+    }
+
+    tree[index].first_child = first_child;
+    tree[index].child_count = moves.len() as u32;
+}
+
+/// Recomputes the proof and disproof numbers on the path from a node to the root.
+fn update_ancestors(tree: &mut [Node], mut index: usize) {
+    loop {
+        let node = &tree[index];
+        let children =
+            &tree[node.first_child as usize..(node.first_child + node.child_count) as usize];
+        let proofs = children.iter().map(|child| child.proof);
+        let disproofs = children.iter().map(|child| child.disproof);
+
+        let (proof, disproof) = if node.attacker_to_move {
+            (proofs.min().unwrap(), sum(disproofs))
+        } else {
+            (sum(proofs), disproofs.min().unwrap())
+        };
+
+        let node = &mut tree[index];
+        node.proof = proof;
+        node.disproof = disproof;
+
+        if index == 0 {
+            break;
+        }
+        index = node.parent as usize;
+    }
+}
+
+/// Sums proof numbers, keeping finite sums below infinity.
+fn sum(numbers: impl Iterator<Item = u32>) -> u32 {
+    let mut total: u32 = 0;
+    for number in numbers {
+        if number == INFINITY {
+            return INFINITY;
+        }
+        total = total.saturating_add(number).min(INFINITY - 1);
+    }
+    total
+}
+
+/// Extracts the quickest proven mate from a proven node with the mating side to move.
+fn solution(tree: &[Node], index: usize) -> Solution {
+    let node = &tree[index];
+    let children = node.first_child as usize..(node.first_child + node.child_count) as usize;
+
+    children
+        .filter(|&child| tree[child].proof == 0)
+        .map(|child| {
+            let reply = &tree[child];
+            let replies =
+                reply.first_child as usize..(reply.first_child + reply.child_count) as usize;
+            Solution {
+                mv: reply.mv.unwrap(),
+                defences: replies
+                    .map(|defence| (tree[defence].mv.unwrap(), solution(tree, defence)))
+                    .collect(),
+            }
+        })
+        .min_by_key(|solution| solution.moves())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, CastlingMode};
+    use std::str::FromStr;
+
+    fn parse_position(fen_str: &str) -> Chess {
+        Fen::from_str(fen_str)
+            .unwrap()
+            .into_position(CastlingMode::Standard)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sum_saturates_below_infinity() {
+        assert_eq!(sum([1, 2].into_iter()), 3);
+        assert_eq!(sum([INFINITY - 1, 5].into_iter()), INFINITY - 1);
+        assert_eq!(sum([1, INFINITY].into_iter()), INFINITY);
+    }
+
+    #[test]
+    fn test_back_rank_mate() {
+        let pos = parse_position("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let (result, _) = solve(&pos, 1, 10_000, || false);
+
+        let MateResult::Mate(solution) = result else {
+            panic!("mate not found");
+        };
+        assert_eq!(solution.moves(), 1);
+        assert!(solution.defences.is_empty());
+    }
+
+    #[test]
+    fn test_stalemate_is_not_mate() {
+        // Qb6 stalemates, and there is no mate in one
+        let pos = parse_position("k7/8/8/2Q5/8/8/8/7K w - - 0 1");
+        assert_eq!(solve(&pos, 1, 10_000, || false).0, MateResult::NoMate);
+    }
+
+    #[test]
+    fn test_mate_in_zero() {
+        let pos = parse_position("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        assert_eq!(solve(&pos, 0, 10_000, || false), (MateResult::NoMate, 0));
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let pos = Chess::default();
+        assert_eq!(solve(&pos, 3, 1_000, || false).0, MateResult::Unknown);
+    }
+}
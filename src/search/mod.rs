@@ -1,21 +1,44 @@
+pub mod context;
+pub mod heuristics;
+pub mod mate;
 pub mod negamax;
+pub mod params;
+pub mod picker;
+pub mod pv;
 pub mod quiescence;
+pub mod root;
 pub mod transposition;
 
 use crate::{
-    eval::order,
-    search::transposition::{Bound, FastTranspositionTable, TranspositionTable},
+    board::Board,
+    eval::evaluator::{AnyEvaluator, Evaluator, EvaluatorKind, StandardEvaluator},
+    search::{
+        context::SearchContext,
+        heuristics::Heuristics,
+        mate::MateResult,
+        params::SearchParams,
+        root::RootMoves,
+        transposition::{Bound, FastTranspositionTable, TranspositionTable},
+    },
     SearchCommand, SearchControl, SearchInfo,
 };
 use crossbeam_channel::{Receiver, Sender};
 use negamax::negamax;
-use shakmaty::{zobrist::Zobrist64, Chess, EnPassantMode, Move, Position};
+use shakmaty::{Chess, Move, Position};
+use std::time::{Duration, Instant};
 
-/// Executes search tasks.
-pub struct Searcher {
+/// Scores at or beyond this magnitude express a forced mate.
+pub const MATE_THRESHOLD: i32 = i32::MAX - 1000;
+
+/// Executes search tasks, evaluating positions with the evaluator it is parameterised by.
+pub struct Searcher<E: Evaluator = StandardEvaluator> {
     cmd_rx: Receiver<SearchCommand>,
     info_tx: Sender<SearchInfo>,
-    tt: FastTranspositionTable,
+    ctx: SearchContext<E>,
+    // Builds the evaluator of a kind selected at runtime, if the searcher can switch to it
+    select: fn(EvaluatorKind) -> Option<E>,
+    // Evaluator selected while searching, switched to once the search is over
+    pending_evaluator: Option<EvaluatorKind>,
 }
 
 #[derive(Clone)]
@@ -24,12 +47,56 @@ pub struct Best {
     pub score: i32,
 }
 
+/// Reasons for abandoning a search before it completes.
+enum Interrupt {
+    // Report the best move found so far, along with a better one from the interrupted iteration
+    Stop(Option<Best>),
+    // Terminate without reporting
+    Quit,
+}
+
 impl Searcher {
     pub fn new(cmd_rx: Receiver<SearchCommand>, info_tx: Sender<SearchInfo>) -> Self {
+        Self::with_params(cmd_rx, info_tx, SearchParams::default())
+    }
+
+    pub fn with_params(
+        cmd_rx: Receiver<SearchCommand>,
+        info_tx: Sender<SearchInfo>,
+        params: SearchParams,
+    ) -> Self {
+        Searcher::with_evaluator(cmd_rx, info_tx, params, StandardEvaluator::default())
+    }
+}
+
+impl Searcher<AnyEvaluator> {
+    /// Creates a searcher that can switch between all evaluators, starting with the given one.
+    pub fn with_evaluator_kind(
+        cmd_rx: Receiver<SearchCommand>,
+        info_tx: Sender<SearchInfo>,
+        params: SearchParams,
+        kind: EvaluatorKind,
+    ) -> Self {
+        Searcher {
+            select: |kind| Some(AnyEvaluator::new(kind)),
+            ..Searcher::with_evaluator(cmd_rx, info_tx, params, AnyEvaluator::new(kind))
+        }
+    }
+}
+
+impl<E: Evaluator> Searcher<E> {
+    pub fn with_evaluator(
+        cmd_rx: Receiver<SearchCommand>,
+        info_tx: Sender<SearchInfo>,
+        params: SearchParams,
+        evaluator: E,
+    ) -> Self {
         Searcher {
             cmd_rx,
             info_tx,
-            tt: FastTranspositionTable::new(28),
+            ctx: SearchContext::with_evaluator(FastTranspositionTable::new(28), params, evaluator),
+            select: |_| None,
+            pending_evaluator: None,
         }
     }
 
@@ -37,24 +104,46 @@ impl Searcher {
     pub fn run(mut self) {
         loop {
             match self.cmd_rx.recv() {
-                Ok(SearchCommand::Start { position, control }) => self.search(position, control),
+                Ok(SearchCommand::Start { position, control }) => {
+                    self.search(position, control);
+                    if let Some(kind) = self.pending_evaluator.take() {
+                        self.set_evaluator(kind);
+                    }
+                }
                 Ok(SearchCommand::Stop) => (),
                 Ok(SearchCommand::Quit) | Err(_) => break,
-                Ok(SearchCommand::Reset) => {
-                    self.tt.clear();
-                }
+                Ok(SearchCommand::Reset) => self.clear(),
+                Ok(SearchCommand::SetEvaluator(kind)) => self.set_evaluator(kind),
+            }
+        }
+    }
+
+    /// Forgets everything learned from earlier searches.
+    fn clear(&mut self) {
+        self.ctx.tt.clear();
+        self.ctx.evaluator.clear();
+        self.ctx.heuristics = Heuristics::default();
+    }
+
+    /// Switches to another evaluator. Scores stored under the previous one are no longer
+    /// comparable, so the searcher starts afresh.
+    fn set_evaluator(&mut self, kind: EvaluatorKind) {
+        match (self.select)(kind) {
+            Some(evaluator) => {
+                self.ctx.evaluator = evaluator;
+                self.clear();
             }
+            None => self.send_string(format!("evaluator {kind} is not available")),
         }
     }
 
     fn search(&mut self, position: Chess, control: SearchControl) {
-        let start_time = std::time::Instant::now();
+        let start_time = Instant::now();
         let mut best = Best {
-            move_: position
+            move_: *position
                 .legal_moves()
                 .first()
-                .expect("No legal moves found")
-                .clone(),
+                .expect("No legal moves found"),
             score: i32::MIN + 1,
         };
 
@@ -62,112 +151,246 @@ impl Searcher {
         let (max_depth, time_limit) = match control {
             SearchControl::ToDepth(depth) => (depth, u64::MAX),
             SearchControl::TimeLimit(time_limit) => (u8::MAX, time_limit),
+            SearchControl::Mate(moves) => return self.solve_mate(position, moves, best.move_),
         };
+        let deadline = start_time.checked_add(Duration::from_millis(time_limit));
+
+        // Set up the board searched by making and unmaking moves
+        let mut board = Board::from(&position);
+        let hash = board.hash();
+        self.ctx.reset_stack();
+        self.ctx.heuristics.age();
 
-        // Hash start position
-        let hash = position.zobrist_hash::<Zobrist64>(EnPassantMode::Legal);
+        // Root moves keep their scores and subtree sizes between iterations
+        let mut root_moves = RootMoves::new(&position, self.ctx.tt.best_move(hash));
 
         // Iterative deepening loop
         'outer: for depth in 1..=max_depth {
-            let mut iteration_best = Best {
-                move_: best.move_.clone(),
-                score: i32::MIN + 1,
-            };
+            self.ctx.nodes = 0;
+            self.ctx.seldepth = 0;
 
-            let mut nodes = 0;
+            // Centre the window on the previous score once it is reliable enough
+            let mut delta = self.ctx.params.aspiration_window;
+            let (mut alpha, mut beta) = if depth >= self.ctx.params.aspiration_min_depth {
+                (
+                    best.score.saturating_sub(delta).max(i32::MIN + 1),
+                    best.score.saturating_add(delta),
+                )
+            } else {
+                (i32::MIN + 1, i32::MAX)
+            };
 
-            // Generate moves from position
-            let mut moves = position.legal_moves();
+            // Re-search with a wider window until the score lands inside it
+            loop {
+                let iteration_best = match self.search_root(
+                    &mut board,
+                    &mut root_moves,
+                    depth,
+                    alpha,
+                    beta,
+                    deadline,
+                ) {
+                    Ok(iteration_best) => iteration_best,
+                    Err(Interrupt::Stop(partial)) => {
+                        // A move that beat the previous best is better even if unfinished
+                        if let Some(partial) = partial {
+                            best = partial;
+                        }
+                        break 'outer;
+                    }
+                    Err(Interrupt::Quit) => return,
+                };
 
-            // Fetch best move from TT if present
-            let mut order_start_index = 0;
-            if let Some(tt_best_move) = self.tt.best_move(hash) {
-                if let Some(i) = moves.iter().position(|m| m == &tt_best_move) {
-                    moves.swap(0, i);
-                    order_start_index = 1;
-                }
-            }
+                delta = delta.saturating_mul(self.ctx.params.aspiration_growth);
+                let open = delta > self.ctx.params.aspiration_max_window;
 
-            // Sort moves
-            moves = order::order(moves, order_start_index);
-
-            for mv in moves {
-                // Get resulting position after move
-                let mut new_pos = position.clone();
-                new_pos.play_unchecked(mv.clone());
-                let hash = new_pos.zobrist_hash(EnPassantMode::Legal);
-
-                // Search from here
-                let score = -negamax(
-                    &new_pos,
-                    depth - 1,
-                    i32::MIN + 1,
-                    -iteration_best.score,
-                    1,
-                    &mut self.tt,
-                    &mut nodes,
-                    hash,
-                );
-
-                // Update results if score has improved
-                if score > iteration_best.score {
-                    iteration_best = Best {
-                        score,
-                        move_: mv.clone(),
+                if iteration_best.score <= alpha && alpha > i32::MIN + 1 {
+                    // Fail low: the true score is at most the reported one
+                    root_moves.sort(best.move_);
+                    self.send_info(depth, vec![best.move_], alpha, self.ctx.nodes, Bound::Upper);
+                    alpha = if open {
+                        i32::MIN + 1
+                    } else {
+                        alpha.saturating_sub(delta).max(i32::MIN + 1)
                     };
+                } else if iteration_best.score >= beta && beta < i32::MAX {
+                    // Fail high: the true score is at least the reported one
+                    root_moves.sort(iteration_best.move_);
+                    self.send_info(
+                        depth,
+                        vec![iteration_best.move_],
+                        beta,
+                        self.ctx.nodes,
+                        Bound::Lower,
+                    );
+                    beta = if open {
+                        i32::MAX
+                    } else {
+                        beta.saturating_add(delta)
+                    };
+                } else {
+                    // Update global best from iteration
+                    root_moves.sort(iteration_best.move_);
+                    best = iteration_best;
+                    break;
                 }
 
-                // Check if allowed time has run out
-                let elapsed = start_time.elapsed();
-                if elapsed > std::time::Duration::from_millis(time_limit) {
-                    break 'outer;
-                }
-
-                // Check for external interrupts
-                match self.cmd_rx.try_recv() {
-                    Ok(SearchCommand::Start { .. }) | Ok(SearchCommand::Stop) => break 'outer,
-                    Ok(SearchCommand::Quit) => return,
-                    _ => (),
-                };
+                self.ctx.nodes = 0;
+                self.ctx.seldepth = 0;
             }
 
-            // Update global best from iteration
-            best = iteration_best;
-
             // Store result in tt
-            self.tt.store(
+            self.ctx.tt.store(
                 hash,
                 best.score,
                 depth,
                 Bound::Exact,
-                best.move_.clone(), // best move found at this node
+                best.move_, // best move found at this node
             );
 
-            // Construct pv
-            let pv = self
-                .tt
-                .pv(position.clone(), Some(best.move_.clone()), depth);
+            // Report the line collected during the search, as far as it is legal
+            let pv = self.ctx.pv.legal_line(position.clone());
 
             // Send info from iteration
-            self.send_info(depth, pv, best.score, nodes);
+            self.send_info(depth, pv, best.score, self.ctx.nodes, Bound::Exact);
         }
 
         // Output best move
         self.info_tx.send(SearchInfo::BestMove(best.move_)).unwrap();
     }
 
-    fn send_info(&self, depth: u8, pv: Vec<Move>, score: i32, nodes: u64) {
+    /// Searches all root moves within the window `(alpha, beta)`, in their current order.
+    /// The returned score is fail-soft, so it may fall outside the window.
+    fn search_root(
+        &mut self,
+        board: &mut Board,
+        root_moves: &mut RootMoves,
+        depth: u8,
+        mut alpha: i32,
+        beta: i32,
+        deadline: Option<Instant>,
+    ) -> Result<Best, Interrupt> {
+        let window_alpha = alpha;
+        self.ctx.pv.clear(0);
+        let mut iteration_best = Best {
+            move_: root_moves.moves[0].mv,
+            score: i32::MIN + 1,
+        };
+
+        for root_move in &mut root_moves.moves {
+            let mv = root_move.mv;
+            self.ctx.stack[0].current_move = Some(mv);
+
+            // Search from the position after the move
+            let nodes_before = self.ctx.nodes;
+            self.ctx.play(board, mv);
+            let score = -negamax(board, depth - 1, -beta, -alpha, 1, &mut self.ctx);
+            self.ctx.undo(board);
+            root_move.score = score;
+            root_move.nodes = self.ctx.nodes - nodes_before;
+
+            // Update results if score has improved
+            if score > iteration_best.score {
+                iteration_best = Best { score, move_: mv };
+                self.ctx.pv.update(0, mv);
+            }
+
+            // Only a move that rose above the window has proven itself better than the previous best
+            let partial = (iteration_best.score > window_alpha).then(|| iteration_best.clone());
+
+            // Check if allowed time has run out
+            if deadline.is_some_and(|deadline| Instant::now() > deadline) {
+                return Err(Interrupt::Stop(partial));
+            }
+
+            // Check for external interrupts
+            match self.cmd_rx.try_recv() {
+                Ok(SearchCommand::Start { .. }) | Ok(SearchCommand::Stop) => {
+                    return Err(Interrupt::Stop(partial))
+                }
+                Ok(SearchCommand::Quit) => return Err(Interrupt::Quit),
+                Ok(SearchCommand::SetEvaluator(kind)) => self.pending_evaluator = Some(kind),
+                _ => (),
+            };
+
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break; // fail high
+            }
+        }
+
+        Ok(iteration_best)
+    }
+
+    /// Proves a forced mate with the mate solver instead of searching for the best move.
+    /// Falls back to the given move if no mate is found.
+    fn solve_mate(&mut self, position: Chess, moves: u8, fallback: Move) {
+        let mut quit = false;
+        let mut pending_evaluator = None;
+        let cmd_rx = &self.cmd_rx;
+        let (result, nodes) =
+            mate::solve(
+                &position,
+                moves,
+                self.ctx.params.mate_max_nodes,
+                || match cmd_rx.try_recv() {
+                    Ok(SearchCommand::Start { .. }) | Ok(SearchCommand::Stop) => true,
+                    Ok(SearchCommand::Quit) => {
+                        quit = true;
+                        true
+                    }
+                    Ok(SearchCommand::SetEvaluator(kind)) => {
+                        pending_evaluator = Some(kind);
+                        false
+                    }
+                    _ => false,
+                },
+            );
+        self.pending_evaluator = pending_evaluator;
+
+        if quit {
+            return;
+        }
+
+        let best_move = match result {
+            MateResult::Mate(solution) => {
+                let pv = solution.main_line();
+                let plies = pv.len() as u8;
+                self.ctx.seldepth = plies;
+                self.send_info(plies, pv, i32::MAX - plies as i32, nodes, Bound::Exact);
+                solution.mv
+            }
+            MateResult::NoMate => {
+                self.send_string(format!("no mate in {moves}"));
+                fallback
+            }
+            MateResult::Unknown => {
+                self.send_string(format!("no mate in {moves} found within the node limit"));
+                fallback
+            }
+        };
+
+        self.info_tx.send(SearchInfo::BestMove(best_move)).unwrap();
+    }
+
+    fn send_string(&self, message: String) {
+        self.info_tx.send(SearchInfo::String(message)).unwrap();
+    }
+
+    fn send_info(&self, depth: u8, pv: Vec<Move>, score: i32, nodes: u64, bound: Bound) {
         self.info_tx
             .send(SearchInfo::Info {
                 depth,
                 pv,
                 score,
+                seldepth: self.ctx.seldepth,
                 nodes,
+                bound,
             })
             .unwrap();
     }
 
     pub fn reset(&mut self) {
-        self.tt = FastTranspositionTable::new(28);
+        self.ctx.tt = FastTranspositionTable::new(28);
     }
 }
@@ -1,26 +1,38 @@
 use crate::{
-    eval::order,
+    board::Board,
+    eval::{evaluator::Evaluator, order},
     search::{
+        context::{SearchContext, MAX_PLY},
+        params::InternalIterative,
+        picker::MovePicker,
         quiescence::quiescence,
-        transposition::{Bound, FastTranspositionTable, TranspositionTable},
+        transposition::{score_to_tt, Bound, TranspositionTable},
+        MATE_THRESHOLD,
     },
 };
-use shakmaty::{zobrist::Zobrist64, Chess, EnPassantMode, Position};
+use shakmaty::{Move, MoveList};
 
-pub fn negamax(
-    board: &Chess,
-    depth: u8,
+pub fn negamax<E: Evaluator>(
+    board: &mut Board,
+    mut depth: u8,
     mut alpha: i32,
     beta: i32,
     ply: u8,
-    tt: &mut FastTranspositionTable,
-    nodes: &mut u64,
-    hash: Zobrist64,
+    ctx: &mut SearchContext<E>,
 ) -> i32 {
-    // Check for TT hit
-    let mut tt_best_move = None;
-    if let Some(entry) = tt.lookup(hash) {
-        tt_best_move = Some(entry.best_move);
+    let hash = board.hash();
+    let excluded_move = ctx.stack[ply as usize].excluded_move;
+    let pv_node = beta > alpha + 1;
+    ctx.pv.clear(ply);
+
+    // Check for TT hit (the entry belongs to the full node, not to a search excluding a move).
+    // PV nodes are searched regardless, so the line below them is collected.
+    let tt_entry = ctx.tt.lookup(hash).map(|entry| entry.at_ply(ply));
+    let mut tt_best_move = tt_entry.as_ref().map(|entry| entry.best_move);
+    if let Some(entry) = tt_entry
+        .as_ref()
+        .filter(|_| excluded_move.is_none() && !pv_node)
+    {
         if entry.depth >= depth {
             match entry.bound {
                 Bound::Exact => return entry.score,
@@ -32,60 +44,231 @@ pub fn negamax(
     }
 
     // Increment nodes count
-    *nodes += 1;
+    ctx.nodes += 1;
+    ctx.seldepth = ctx.seldepth.max(ply);
+
+    // Leaf node
+    if depth == 0 || ply as usize >= MAX_PLY {
+        return quiescence(board, alpha, beta, ply, ctx);
+    }
 
-    // Terminal node
-    if depth == 0 || board.is_game_over() {
-        return quiescence(board, alpha, beta, ply);
+    // Draws by rule are found without generating moves
+    if board.is_draw() {
+        return 0;
     }
 
-    let mut moves = board.legal_moves();
+    let in_check = board.is_check();
+    let window_is_mate = alpha <= -MATE_THRESHOLD || beta >= MATE_THRESHOLD;
+    let extensions = ctx.stack[ply as usize].extensions;
+
+    // Without a TT move the move ordering is poor, so find one with a shallower search first,
+    // or spend less effort on the node as it was not important enough to be stored.
+    // Reductions are not applied while searching for mates, which must not be missed.
+    if tt_best_move.is_none() && excluded_move.is_none() {
+        match ctx.params.internal_iterative {
+            InternalIterative::Deepening if pv_node && depth >= ctx.params.iid_min_depth => {
+                let iid_depth = depth.saturating_sub(ctx.params.iid_reduction);
+                negamax(board, iid_depth, alpha, beta, ply, ctx);
+                tt_best_move = ctx.tt.best_move(hash);
+            }
+            InternalIterative::Reductions
+                if depth >= ctx.params.iir_min_depth && !window_is_mate =>
+            {
+                depth -= 1
+            }
+            _ => {}
+        }
+    }
+
+    // Static evaluation for pruning decisions (not meaningful when in check)
+    let static_eval = (!in_check && excluded_move.is_none()).then(|| ctx.evaluator.evaluate(board));
+    let prunable_eval = static_eval.filter(|eval| eval.abs() < MATE_THRESHOLD && !window_is_mate);
+
+    if let Some(eval) = prunable_eval.filter(|_| !pv_node) {
+        // Reverse futility pruning: the position is good enough that beta will hold anyway
+        if depth <= ctx.params.rfp_max_depth && eval - ctx.params.rfp_margin * depth as i32 >= beta
+        {
+            return eval;
+        }
+
+        // Razoring: hopeless positions are resolved by quiescence search alone
+        if depth <= ctx.params.razor_max_depth
+            && eval + ctx.params.razor_margin * depth as i32 <= alpha
+        {
+            let score = quiescence(board, alpha, beta, ply, ctx);
+            if score <= alpha {
+                return score;
+            }
+        }
+    }
+
+    // Null move pruning: if passing the turn still beats beta, a real move will too
+    let after_null_move = ply > 0 && ctx.stack[ply as usize - 1].null_move;
+    if ctx.params.null_move
+        && depth >= ctx.params.null_move_min_depth
+        && ply >= ctx.null_move_min_ply
+        && !after_null_move
+        && excluded_move.is_none()
+        && beta < MATE_THRESHOLD
+        && !in_check
+        && !board.accumulator().phase.is_pawn_endgame()
+    {
+        if let Some(score) = null_move_search(board, depth, beta, ply, ctx) {
+            return score;
+        }
+    }
+
+    // ProbCut: a good capture beating beta by a margin in a shallow search will very likely
+    // beat beta at full depth too
+    if let Some(eval) = prunable_eval.filter(|_| !pv_node) {
+        let probcut_beta = beta.saturating_add(ctx.params.probcut_margin);
+        let probcut_depth = depth.saturating_sub(ctx.params.probcut_reduction) + 1;
+        let tt_refutes = tt_entry
+            .as_ref()
+            .is_some_and(|entry| entry.depth >= probcut_depth && entry.score < probcut_beta);
+
+        if depth >= ctx.params.probcut_min_depth && probcut_beta < MATE_THRESHOLD && !tt_refutes {
+            if let Some(score) = probcut(board, depth, probcut_beta, eval, ply, ctx) {
+                return score;
+            }
+        }
+    }
+
+    // Quiet moves are futile at frontier nodes if even an optimistic margin cannot raise alpha
+    let futility_value = prunable_eval
+        .filter(|_| depth <= ctx.params.futility_max_depth)
+        .map(|eval| eval + ctx.params.futility_margin * depth as i32)
+        .filter(|&value| value <= alpha);
+
+    // Singular extension: extend the TT move if every alternative falls clearly short of its score
+    let singular_move = tt_entry.filter(|entry| {
+        excluded_move.is_none()
+            && depth >= ctx.params.singular_min_depth
+            && entry.bound != Bound::Upper
+            && entry.depth + 3 >= depth
+            && entry.score.abs() < MATE_THRESHOLD
+            && extensions < ctx.params.max_extensions
+            && is_singular(board, depth, entry.score, entry.best_move, ply, ctx)
+    });
 
     let mut best_score = i32::MIN + 1;
-    let mut best_move = moves.first().unwrap().clone();
+    let mut best_move = None;
     let alpha_orig = alpha;
 
-    // Fetch best move from TT if present
-    let mut order_start_index = 0;
-    if let Some(tt_move) = tt_best_move {
-        if let Some(i) = moves.iter().position(|m| m == &tt_move) {
-            moves.swap(0, i);
-            order_start_index = 1;
+    // Pick moves in stages, ranking quiet moves by how often they caused cutoffs elsewhere
+    let previous_move = ply
+        .checked_sub(1)
+        .and_then(|previous_ply| ctx.stack[previous_ply as usize].current_move);
+    let mut picker = MovePicker::new(tt_best_move);
+
+    // Quiet moves searched without causing a cutoff
+    let mut failed_quiets = MoveList::new();
+    // Whether any legal move was picked, including pruned and excluded ones
+    let mut has_moves = false;
+
+    for move_index in 0.. {
+        let Some(mv) = picker.next(board, &ctx.heuristics, ply, previous_move) else {
+            break;
+        };
+        has_moves = true;
+        if excluded_move == Some(mv) {
+            continue;
         }
-    }
 
-    // Sort moves
-    moves = order::order(moves, order_start_index);
-
-    for mv in moves {
-        let mut new_pos = board.clone();
-        new_pos.play_unchecked(mv.clone());
-
-        let new_hash =
-            match board.update_zobrist_hash::<Zobrist64>(hash, mv.clone(), EnPassantMode::Legal) {
-                Some(h) => h,
-                None => new_pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
-            };
-        let score = -negamax(
-            &mut new_pos,
-            depth - 1,
-            -beta,
-            -alpha,
-            ply + 1,
-            tt,
-            nodes,
-            new_hash,
-        );
+        let quiet = !mv.is_capture() && !mv.is_promotion();
+
+        let gives_check = board.gives_check(mv);
+
+        // Late move pruning: skip the tail of the quiet moves at shallow depth
+        if quiet
+            && !in_check
+            && !gives_check
+            && depth <= ctx.params.lmp_max_depth
+            && move_index >= ctx.late_move_count(depth)
+            && best_score > -MATE_THRESHOLD
+        {
+            continue;
+        }
+
+        // Futility pruning
+        if let Some(value) = futility_value.filter(|_| quiet && !gives_check && move_index > 0) {
+            best_score = best_score.max(value);
+            continue;
+        }
+
+        ctx.stack[ply as usize].current_move = Some(mv);
+        ctx.stack[ply as usize].null_move = false;
+
+        // Search forcing moves deeper while the line has extension budget left
+        let extension = if extensions < ctx.params.max_extensions
+            && ((ctx.params.check_extension && gives_check)
+                || singular_move
+                    .as_ref()
+                    .is_some_and(|entry| entry.best_move == mv))
+        {
+            1
+        } else {
+            0
+        };
+        ctx.stack[ply as usize + 1].extensions = extensions + extension;
+        let new_depth = depth - 1 + extension;
+
+        // Late move reductions: search late quiet moves shallower with a null window first
+        let reduction = if quiet
+            && !in_check
+            && !gives_check
+            && depth >= ctx.params.lmr_min_depth
+            && move_index >= ctx.params.lmr_min_move_index
+        {
+            ctx.reduction(depth, move_index)
+                .min(depth.saturating_sub(2))
+        } else {
+            0
+        };
+
+        ctx.play(board, mv);
+        let mut score = i32::MIN + 1;
+        if reduction > 0 {
+            let reduced_depth = new_depth - reduction;
+            score = -negamax(board, reduced_depth, -alpha - 1, -alpha, ply + 1, ctx);
+        }
+
+        // Search at full depth unless the reduced search failed low
+        if reduction == 0 || score > alpha {
+            score = -negamax(board, new_depth, -beta, -alpha, ply + 1, ctx);
+        }
+        ctx.undo(board);
 
         if score > best_score {
             best_score = score;
-            best_move = mv;
+            best_move = Some(mv);
+        }
+
+        if score > alpha {
+            ctx.pv.update(ply, mv);
         }
 
         alpha = alpha.max(score);
         if alpha >= beta {
+            if quiet {
+                ctx.heuristics
+                    .update(board.turn(), ply, depth, previous_move, mv, &failed_quiets);
+            }
             break; // beta cutoff
         }
+
+        if quiet {
+            failed_quiets.push(mv);
+        }
+    }
+
+    // Checkmate or stalemate: no legal moves exist
+    if !has_moves {
+        return if in_check {
+            i32::MIN + 1 + ply as i32
+        } else {
+            0
+        };
     }
 
     // Store TT entry
@@ -97,7 +280,127 @@ pub fn negamax(
         Bound::Exact
     };
 
-    tt.store(hash, best_score, depth, bound, best_move);
+    // Every legal move is searched or pruned after the first, unless the first was excluded
+    if let (None, Some(best_move)) = (excluded_move, best_move) {
+        ctx.tt
+            .store(hash, score_to_tt(best_score, ply), depth, bound, best_move);
+    }
 
     best_score
 }
+
+/// Searches captures winning enough material to reach the raised beta at reduced depth.
+/// Returns the score to cut off with if one of them still beats it, storing it as a lower bound.
+fn probcut<E: Evaluator>(
+    board: &mut Board,
+    depth: u8,
+    probcut_beta: i32,
+    eval: i32,
+    ply: u8,
+    ctx: &mut SearchContext<E>,
+) -> Option<i32> {
+    let reduced_depth = depth.saturating_sub(ctx.params.probcut_reduction);
+    let hash = board.hash();
+
+    for (mv, exchange) in order::order_with_exchange(board.capture_moves(), board.board()) {
+        if eval + exchange < probcut_beta {
+            continue;
+        }
+
+        ctx.play(board, mv);
+        ctx.stack[ply as usize].current_move = Some(mv);
+        ctx.stack[ply as usize].null_move = false;
+        ctx.stack[ply as usize + 1].extensions = ctx.stack[ply as usize].extensions;
+
+        // Confirm with quiescence search before spending a reduced search
+        let mut score = -quiescence(board, -probcut_beta, -probcut_beta + 1, ply + 1, ctx);
+        if score >= probcut_beta {
+            score = -negamax(
+                board,
+                reduced_depth,
+                -probcut_beta,
+                -probcut_beta + 1,
+                ply + 1,
+                ctx,
+            );
+        }
+        ctx.undo(board);
+
+        if score >= probcut_beta {
+            ctx.tt
+                .store(hash, score, reduced_depth + 1, Bound::Lower, mv);
+            return Some(score);
+        }
+    }
+
+    None
+}
+
+/// Searches the node at reduced depth without the TT move.
+/// The TT move is singular if no other move comes close to its score.
+fn is_singular<E: Evaluator>(
+    board: &mut Board,
+    depth: u8,
+    tt_score: i32,
+    tt_move: Move,
+    ply: u8,
+    ctx: &mut SearchContext<E>,
+) -> bool {
+    let singular_beta = tt_score - ctx.params.singular_margin * depth as i32;
+
+    ctx.stack[ply as usize].excluded_move = Some(tt_move);
+    let score = negamax(
+        board,
+        (depth - 1) / 2,
+        singular_beta - 1,
+        singular_beta,
+        ply,
+        ctx,
+    );
+    ctx.stack[ply as usize].excluded_move = None;
+
+    score < singular_beta
+}
+
+/// Passes the turn and searches the position at reduced depth with a null window around beta.
+/// Returns the score to cut off with if the side to move still fails high.
+fn null_move_search<E: Evaluator>(
+    board: &mut Board,
+    depth: u8,
+    beta: i32,
+    ply: u8,
+    ctx: &mut SearchContext<E>,
+) -> Option<i32> {
+    // Reduce more at higher depths
+    let reduction = ctx.params.null_move_reduction + depth / ctx.params.null_move_depth_divisor;
+    let null_depth = depth.saturating_sub(1 + reduction);
+
+    ctx.stack[ply as usize].current_move = None;
+    ctx.stack[ply as usize].null_move = true;
+    ctx.stack[ply as usize + 1].extensions = ctx.stack[ply as usize].extensions;
+    ctx.play_null(board);
+    let score = -negamax(board, null_depth, -beta, -beta + 1, ply + 1, ctx);
+    ctx.undo(board);
+    ctx.stack[ply as usize].null_move = false;
+
+    if score < beta {
+        return None;
+    }
+
+    // Mate scores found after passing the turn are not proven
+    let score = if score >= MATE_THRESHOLD { beta } else { score };
+
+    // Verify at high depths by searching this node without null moves near the root of the subtree
+    match ctx.params.null_move_verification_depth {
+        Some(verification_depth) if depth >= verification_depth => {
+            let min_ply = ctx.null_move_min_ply;
+            let min_verified_ply = ply as usize + 1 + null_depth as usize * 3 / 4;
+            ctx.null_move_min_ply = min_verified_ply.min(MAX_PLY) as u8;
+            let verified = negamax(board, null_depth, beta - 1, beta, ply, ctx);
+            ctx.null_move_min_ply = min_ply;
+
+            (verified >= beta).then_some(score)
+        }
+        _ => Some(score),
+    }
+}
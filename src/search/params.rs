@@ -0,0 +1,127 @@
+/// Handling of nodes without a move from the TT to search first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InternalIterative {
+    // Rely on the static move ordering alone
+    Disabled,
+    // Search the node at reduced depth first to find a move to search first
+    Deepening,
+    // Search the node one ply shallower, expecting it to be less important
+    Reductions,
+}
+
+/// Tunable parameters of the search.
+#[derive(Clone, Debug)]
+pub struct SearchParams {
+    /// Minimum depth at which iterations start with an aspiration window
+    pub aspiration_min_depth: u8,
+    /// Initial distance from the previous score to each side of the window
+    pub aspiration_window: i32,
+    /// Factor by which the failing side of the window is widened on each re-search
+    pub aspiration_growth: i32,
+    /// Widths beyond which the failing side of the window is opened completely
+    pub aspiration_max_window: i32,
+    /// Whether to try passing the turn to prove a cutoff
+    pub null_move: bool,
+    /// Minimum remaining depth for trying a null move
+    pub null_move_min_depth: u8,
+    /// Base depth reduction of the null move search
+    pub null_move_reduction: u8,
+    /// The reduction grows by one ply for every this many plies of depth
+    pub null_move_depth_divisor: u8,
+    /// Minimum remaining depth for verifying null move cutoffs with a regular search
+    pub null_move_verification_depth: Option<u8>,
+    /// Minimum remaining depth for reducing late moves
+    pub lmr_min_depth: u8,
+    /// Number of moves searched at full depth before reductions start
+    pub lmr_min_move_index: usize,
+    /// Constant term of the logarithmic reduction formula
+    pub lmr_base: f32,
+    /// Divisor of the logarithmic term of the reduction formula
+    pub lmr_divisor: f32,
+    /// Maximum remaining depth for pruning late quiet moves
+    pub lmp_max_depth: u8,
+    /// Number of quiet moves searched before pruning at depth 1, growing quadratically with depth
+    pub lmp_base: usize,
+    /// Maximum remaining depth for returning early when the static evaluation beats beta
+    pub rfp_max_depth: u8,
+    /// Margin per ply of depth by which the static evaluation must beat beta
+    pub rfp_margin: i32,
+    /// Maximum remaining depth for skipping quiet moves that cannot raise alpha
+    pub futility_max_depth: u8,
+    /// Margin per ply of depth a quiet move is assumed to gain at most
+    pub futility_margin: i32,
+    /// Maximum remaining depth for dropping into quiescence search when far below alpha
+    pub razor_max_depth: u8,
+    /// Margin per ply of depth by which the static evaluation must trail alpha
+    pub razor_margin: i32,
+    /// Whether to search moves that give check one ply deeper
+    pub check_extension: bool,
+    /// Minimum remaining depth for testing whether the TT move is singular
+    pub singular_min_depth: u8,
+    /// Margin per ply of depth by which all other moves must trail the TT score
+    pub singular_margin: i32,
+    /// Maximum number of plies by which a single line may be extended
+    pub max_extensions: u8,
+    /// Whether quiescence search tries quiet moves giving check on its first ply
+    pub qsearch_checks: bool,
+    /// Margin above the material a capture wins for it to be searched in quiescence
+    pub delta_margin: i32,
+    /// Maximum size of the proof tree built by the mate solver
+    pub mate_max_nodes: usize,
+    /// How nodes without a TT move are treated
+    pub internal_iterative: InternalIterative,
+    /// Minimum depth for internal iterative deepening at PV nodes
+    pub iid_min_depth: u8,
+    /// Depth reduction of the internal iterative deepening search
+    pub iid_reduction: u8,
+    /// Minimum depth for internal iterative reductions
+    pub iir_min_depth: u8,
+    /// Minimum depth for ProbCut
+    pub probcut_min_depth: u8,
+    /// Margin above beta that a capture must reach in the shallow ProbCut search
+    pub probcut_margin: i32,
+    /// Depth reduction of the ProbCut search
+    pub probcut_reduction: u8,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        SearchParams {
+            aspiration_min_depth: 4,
+            aspiration_window: 25,
+            aspiration_growth: 3,
+            aspiration_max_window: 1000,
+            null_move: true,
+            null_move_min_depth: 3,
+            null_move_reduction: 2,
+            null_move_depth_divisor: 4,
+            null_move_verification_depth: Some(10),
+            lmr_min_depth: 3,
+            lmr_min_move_index: 3,
+            lmr_base: 0.75,
+            lmr_divisor: 2.25,
+            lmp_max_depth: 3,
+            lmp_base: 4,
+            rfp_max_depth: 6,
+            rfp_margin: 80,
+            futility_max_depth: 3,
+            futility_margin: 100,
+            razor_max_depth: 2,
+            razor_margin: 250,
+            check_extension: true,
+            singular_min_depth: 8,
+            singular_margin: 2,
+            max_extensions: 16,
+            qsearch_checks: true,
+            delta_margin: 200,
+            mate_max_nodes: 4_000_000,
+            internal_iterative: InternalIterative::Reductions,
+            iid_min_depth: 5,
+            iid_reduction: 2,
+            iir_min_depth: 4,
+            probcut_min_depth: 5,
+            probcut_margin: 200,
+            probcut_reduction: 4,
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{
+    board::Board,
+    eval::{order::kind_score, see::see},
+    search::heuristics::Heuristics,
+};
+use shakmaty::{Move, MoveList};
+
+/// Most legal moves possible in a position, the capacity of a `MoveList`.
+const MAX_MOVES: usize = 270;
+
+/// Stages of move picking, in the order they are visited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    // Move from the transposition table, tried before any moves are generated
+    TtMove,
+    // Generate captures and promotions and sort them into good and bad ones by SEE
+    GenerateNoisy,
+    // Captures not losing material and promotions, by MVV-LVA
+    GoodNoisy,
+    // Quiet moves that caused cutoffs at the same ply elsewhere
+    Killers,
+    // Generate and score the quiet moves that are not killers
+    GenerateQuiets,
+    // Quiet moves by countermove and history
+    Quiets,
+    // Captures losing material, by SEE
+    BadNoisy,
+    Done,
+}
+
+/// Yields the moves of a node one at a time, best first, generating and scoring each stage only
+/// once it is reached so a cutoff early in the list skips the remaining work.
+pub struct MovePicker {
+    stage: Stage,
+    tt_move: Option<Move>,
+    // Generated moves, partitioned into good noisy, bad noisy and quiet moves
+    moves: MoveList,
+    // Killers already picked, left out of the quiet moves
+    killers: [Option<Move>; 2],
+    // Next killer slot to consider
+    killer_index: usize,
+    // Ordering score of the move at the same index
+    scores: [i32; MAX_MOVES],
+    // Next move to consider in the current stage
+    cursor: usize,
+    // End of the good noisy moves and start of the bad noisy moves
+    good_end: usize,
+    // End of the noisy moves and start of the quiet moves
+    noisy_end: usize,
+}
+
+impl MovePicker {
+    pub fn new(tt_move: Option<Move>) -> Self {
+        MovePicker {
+            stage: Stage::TtMove,
+            tt_move,
+            moves: MoveList::new(),
+            killers: [None; 2],
+            killer_index: 0,
+            scores: [0; MAX_MOVES],
+            cursor: 0,
+            good_end: 0,
+            noisy_end: 0,
+        }
+    }
+
+    /// Returns the next move to search, or `None` once all legal moves have been picked.
+    /// Quiet moves are ranked with the heuristics of the given ply and previous move.
+    pub fn next(
+        &mut self,
+        board: &Board,
+        heuristics: &Heuristics,
+        ply: u8,
+        previous: Option<Move>,
+    ) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::TtMove => {
+                    self.stage = Stage::GenerateNoisy;
+
+                    // The entry may belong to another position sharing the hash slot
+                    self.tt_move = self.tt_move.filter(|&mv| board.is_legal(mv));
+                    if self.tt_move.is_some() {
+                        return self.tt_move;
+                    }
+                }
+                Stage::GenerateNoisy => {
+                    self.stage = Stage::GoodNoisy;
+                    self.generate_noisy(board);
+                }
+                Stage::GoodNoisy => {
+                    if let Some(mv) = self.pick_best(self.good_end) {
+                        return Some(mv);
+                    }
+                    self.stage = Stage::Killers;
+                    self.cursor = self.noisy_end;
+                }
+                Stage::Killers => {
+                    // Killers are checked for legality here, as quiet moves are not generated yet
+                    let killers = heuristics.killers(ply);
+                    if let Some(&killer) = killers.get(self.killer_index) {
+                        self.killer_index += 1;
+                        let playable = killer.filter(|&killer| {
+                            self.tt_move != Some(killer)
+                                && !killer.is_capture()
+                                && !killer.is_promotion()
+                                && board.is_legal(killer)
+                        });
+                        if playable.is_some() {
+                            self.killers[self.killer_index - 1] = playable;
+                            return playable;
+                        }
+                    } else {
+                        self.stage = Stage::GenerateQuiets;
+                    }
+                }
+                Stage::GenerateQuiets => {
+                    self.stage = Stage::Quiets;
+                    for mv in board.quiet_moves() {
+                        if self.tt_move != Some(mv) && !self.killers.contains(&Some(mv)) {
+                            self.moves.push(mv);
+                        }
+                    }
+                    for i in self.cursor..self.moves.len() {
+                        self.scores[i] =
+                            heuristics.quiet_score(board.turn(), ply, previous, &self.moves[i]);
+                    }
+                }
+                Stage::Quiets => {
+                    if let Some(mv) = self.pick_best(self.moves.len()) {
+                        return Some(mv);
+                    }
+                    self.stage = Stage::BadNoisy;
+                    self.cursor = self.good_end;
+                }
+                Stage::BadNoisy => {
+                    if let Some(mv) = self.pick_best(self.noisy_end) {
+                        return Some(mv);
+                    }
+                    self.stage = Stage::Done;
+                }
+                Stage::Done => return None,
+            }
+        }
+    }
+
+    /// Generates the captures and promotions except the TT move and scores them.
+    /// Leaves the cursor at the start of the good noisy moves.
+    fn generate_noisy(&mut self, board: &Board) {
+        self.moves = board.noisy_moves();
+        if let Some(tt_move) = self.tt_move {
+            self.moves.retain(|mv| *mv != tt_move);
+        }
+
+        // Move the noisy moves losing material behind the others
+        for i in 0..self.moves.len() {
+            let mv = self.moves[i];
+            let exchange = if mv.is_capture() {
+                see(board.board(), mv)
+            } else {
+                0
+            };
+
+            if exchange < 0 {
+                self.scores[self.noisy_end] = exchange;
+            } else {
+                self.moves.swap(self.good_end, self.noisy_end);
+                self.scores[self.noisy_end] = self.scores[self.good_end];
+                self.scores[self.good_end] = kind_score(&mv);
+                self.good_end += 1;
+            }
+            self.noisy_end += 1;
+        }
+
+        self.cursor = 0;
+    }
+
+    /// Moves the best scored move between the cursor and the end of the stage to the cursor
+    /// and returns it.
+    fn pick_best(&mut self, end: usize) -> Option<Move> {
+        if self.cursor >= end {
+            return None;
+        }
+
+        let best = (self.cursor..end).max_by_key(|&i| self.scores[i])?;
+        self.moves.swap(self.cursor, best);
+        self.scores.swap(self.cursor, best);
+        self.cursor += 1;
+
+        Some(self.moves[self.cursor - 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{fen::Fen, uci::UciMove, CastlingMode, Chess, Position, Role};
+    use std::str::FromStr;
+
+    fn parse_position(fen_str: &str) -> Chess {
+        Fen::from_str(fen_str)
+            .unwrap()
+            .into_position(CastlingMode::Standard)
+            .unwrap()
+    }
+
+    fn parse_move(pos: &Chess, uci: &str) -> Move {
+        UciMove::from_ascii(uci.as_bytes())
+            .unwrap()
+            .to_move(pos)
+            .unwrap()
+    }
+
+    fn pick_all(
+        picker: &mut MovePicker,
+        pos: &Chess,
+        heuristics: &Heuristics,
+        ply: u8,
+    ) -> Vec<Move> {
+        let board = Board::from(pos);
+        std::iter::from_fn(|| picker.next(&board, heuristics, ply, None)).collect()
+    }
+
+    #[test]
+    fn test_picks_every_legal_move_once() {
+        let pos =
+            parse_position("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let tt_move = parse_move(&pos, "e2a6");
+        let mut picked = pick_all(
+            &mut MovePicker::new(Some(tt_move)),
+            &pos,
+            &Heuristics::default(),
+            0,
+        );
+
+        assert_eq!(picked[0], tt_move);
+
+        let mut legal = pos.legal_moves().to_vec();
+        let key = |mv: &Move| mv.to_string();
+        picked.sort_by_key(key);
+        legal.sort_by_key(key);
+        assert_eq!(picked, legal);
+    }
+
+    #[test]
+    fn test_illegal_tt_move_is_skipped() {
+        let pos = parse_position("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        let foreign = Move::Normal {
+            role: Role::Queen,
+            from: shakmaty::Square::D1,
+            capture: None,
+            to: shakmaty::Square::D8,
+            promotion: None,
+        };
+        let picked = pick_all(
+            &mut MovePicker::new(Some(foreign)),
+            &pos,
+            &Heuristics::default(),
+            0,
+        );
+
+        assert!(!picked.contains(&foreign));
+        assert_eq!(picked.len(), pos.legal_moves().len());
+    }
+
+    #[test]
+    fn test_stage_order() {
+        // Qxd5 loses the queen to exd5, Nxe5 wins a pawn, Ng5 is a killer
+        let pos = parse_position("4k3/8/4p3/3pp3/8/5N2/8/3QK3 w - - 0 1");
+        let good_capture = parse_move(&pos, "f3e5");
+        let bad_capture = parse_move(&pos, "d1d5");
+        let killer = parse_move(&pos, "f3g5");
+
+        let mut heuristics = Heuristics::default();
+        heuristics.update(pos.turn(), 2, 3, None, killer, &[]);
+
+        let picked = pick_all(&mut MovePicker::new(None), &pos, &heuristics, 2);
+
+        assert_eq!(picked[0], good_capture);
+        assert_eq!(picked[1], killer);
+        assert_eq!(picked.last(), Some(&bad_capture));
+        assert_eq!(picked.len(), pos.legal_moves().len());
+    }
+
+    #[test]
+    fn test_quiets_are_generated_after_killers() {
+        let pos = parse_position("4k3/8/4p3/3pp3/8/5N2/8/3QK3 w - - 0 1");
+        let board = Board::from(&pos);
+        let killer = parse_move(&pos, "f3g5");
+        let mut heuristics = Heuristics::default();
+        heuristics.update(pos.turn(), 2, 3, None, killer, &[]);
+
+        let mut picker = MovePicker::new(None);
+        assert_eq!(
+            picker.next(&board, &heuristics, 2, None),
+            Some(parse_move(&pos, "f3e5"))
+        );
+        assert_eq!(picker.next(&board, &heuristics, 2, None), Some(killer));
+        // Only the captures have been generated so far
+        assert_eq!(picker.moves.len(), board.noisy_moves().len());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::search::context::MAX_PLY;
+use shakmaty::{Chess, Move, Position};
+
+/// Triangular table of principal variations.
+/// Row `ply` holds the best line found from the node currently searched at that ply, built by
+/// prefixing the best move to the line of the row below.
+pub struct PvTable {
+    moves: [[Option<Move>; MAX_PLY + 1]; MAX_PLY + 1],
+    lengths: [usize; MAX_PLY + 1],
+}
+
+impl Default for PvTable {
+    fn default() -> Self {
+        PvTable {
+            moves: [[None; MAX_PLY + 1]; MAX_PLY + 1],
+            lengths: [0; MAX_PLY + 1],
+        }
+    }
+}
+
+impl PvTable {
+    /// Empties the line of a node that is about to be searched.
+    #[inline(always)]
+    pub fn clear(&mut self, ply: u8) {
+        self.lengths[ply as usize] = 0;
+    }
+
+    /// Makes a move followed by the line of its child the line of the node at the given ply.
+    #[inline(always)]
+    pub fn update(&mut self, ply: u8, mv: Move) {
+        let ply = ply as usize;
+        let child_length = if ply < MAX_PLY {
+            self.lengths[ply + 1]
+        } else {
+            0
+        };
+
+        let (row, rest) = self.moves[ply..].split_first_mut().unwrap();
+        row[0] = Some(mv);
+        if let Some(child_row) = rest.first() {
+            row[1..=child_length].copy_from_slice(&child_row[..child_length]);
+        }
+        self.lengths[ply] = child_length + 1;
+    }
+
+    /// The line found from the node at the given ply.
+    pub fn line(&self, ply: u8) -> Vec<Move> {
+        self.moves[ply as usize][..self.lengths[ply as usize]]
+            .iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// The line found from the root, cut off before the first move that is not legal.
+    pub fn legal_line(&self, mut pos: Chess) -> Vec<Move> {
+        let mut line = self.line(0);
+        let legal_length = line
+            .iter()
+            .take_while(|&&mv| {
+                let legal = pos.is_legal(mv);
+                if legal {
+                    pos.play_unchecked(mv);
+                }
+                legal
+            })
+            .count();
+        line.truncate(legal_length);
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{Role, Square};
+
+    fn quiet(from: Square, to: Square, role: Role) -> Move {
+        Move::Normal {
+            role,
+            from,
+            capture: None,
+            to,
+            promotion: None,
+        }
+    }
+
+    #[test]
+    fn test_line_is_built_from_child() {
+        let mut pv = PvTable::default();
+        let (e4, e5, nf3) = (
+            quiet(Square::E2, Square::E4, Role::Pawn),
+            quiet(Square::E7, Square::E5, Role::Pawn),
+            quiet(Square::G1, Square::F3, Role::Knight),
+        );
+
+        pv.clear(3);
+        pv.update(2, nf3);
+        pv.update(1, e5);
+        pv.update(0, e4);
+        assert_eq!(pv.line(0), vec![e4, e5, nf3]);
+
+        // A new best move at the root replaces the whole line
+        pv.clear(1);
+        pv.update(0, nf3);
+        assert_eq!(pv.line(0), vec![nf3]);
+    }
+
+    #[test]
+    fn test_legal_line_stops_at_illegal_move() {
+        let mut pv = PvTable::default();
+        let (e4, illegal, nf3) = (
+            quiet(Square::E2, Square::E4, Role::Pawn),
+            quiet(Square::E2, Square::E4, Role::Pawn),
+            quiet(Square::G1, Square::F3, Role::Knight),
+        );
+
+        pv.clear(3);
+        pv.update(2, nf3);
+        pv.update(1, illegal);
+        pv.update(0, e4);
+        assert_eq!(pv.legal_line(Chess::default()), vec![e4]);
+    }
+}
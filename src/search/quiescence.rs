@@ -1,22 +1,203 @@
-use crate::eval::evaluate;
-use shakmaty::{Chess, Position};
-
-pub fn quiescence(board: &Chess, alpha: i32, beta: i32, ply: u8) -> i32 {
-    let stand_pat = evaluate(board, ply);
-    if stand_pat >= beta {
-        return beta;
-    }
-    let mut alpha = alpha.max(stand_pat);
-
-    for mv in board.capture_moves() {
-        let mut new_board = board.clone();
-        new_board.play_unchecked(mv.clone());
-        let score = -quiescence(&new_board, -beta, -alpha, ply + 1);
-        if score >= beta {
-            return beta;
+use crate::{
+    board::Board,
+    eval::{evaluator::Evaluator, material::material_score, order},
+    search::{
+        context::{SearchContext, MAX_PLY},
+        transposition::{score_to_tt, Bound, TranspositionTable},
+    },
+};
+use shakmaty::{Move, MoveList, Role};
+
+/// Searches captures and promotions until the position is quiet, so the static evaluation is
+/// only trusted where no immediate tactics are pending.
+pub fn quiescence<E: Evaluator>(
+    board: &mut Board,
+    alpha: i32,
+    beta: i32,
+    ply: u8,
+    ctx: &mut SearchContext<E>,
+) -> i32 {
+    search(board, alpha, beta, ply, 0, ctx)
+}
+
+fn search<E: Evaluator>(
+    board: &mut Board,
+    mut alpha: i32,
+    beta: i32,
+    ply: u8,
+    qply: u8,
+    ctx: &mut SearchContext<E>,
+) -> i32 {
+    let hash = board.hash();
+
+    // Quiescence moves are not part of the reported line
+    ctx.pv.clear(ply);
+
+    // Draws by rule are found before a stored score can hide them
+    if board.is_draw() {
+        return 0;
+    }
+
+    // Check for TT hit (any entry searched at least as deep as quiescence will do)
+    let tt_entry = ctx.tt.lookup(hash).map(|entry| entry.at_ply(ply));
+    if let Some(entry) = tt_entry.as_ref() {
+        match entry.bound {
+            Bound::Exact => return entry.score,
+            Bound::Lower if entry.score >= beta => return entry.score,
+            Bound::Upper if entry.score <= alpha => return entry.score,
+            _ => {}
         }
+    }
+
+    ctx.nodes += 1;
+    ctx.seldepth = ctx.seldepth.max(ply);
+
+    if ply as usize >= MAX_PLY {
+        return ctx.evaluator.evaluate(board);
+    }
+
+    let in_check = board.is_check();
+    let alpha_orig = alpha;
+
+    // Checkmate or stalemate, before standing pat on an evaluation that cannot be reached
+    let moves = board.legal_moves();
+    if moves.is_empty() {
+        return if in_check {
+            i32::MIN + 1 + ply as i32
+        } else {
+            0
+        };
+    }
+
+    // Stand pat: the side to move may decline every capture, unless it has to escape check
+    let stand_pat = (!in_check).then(|| ctx.evaluator.evaluate(board));
+    let mut best_score = stand_pat.unwrap_or(i32::MIN + 1);
+    if best_score >= beta {
+        return best_score;
+    }
+    alpha = alpha.max(best_score);
+
+    let moves = noisy_moves(
+        board,
+        moves,
+        in_check,
+        qply == 0 && ctx.params.qsearch_checks,
+    );
+
+    let mut best_move = None;
+    for (mv, exchange) in order::order_with_exchange(moves, board.board()) {
+        // Moves losing material in the exchange cannot improve on standing pat
+        if stand_pat.is_some() && exchange < 0 {
+            continue;
+        }
+
+        // Delta pruning: even winning the exchange outright leaves the score below alpha
+        if stand_pat.is_some_and(|stand_pat| {
+            stand_pat + material_gain(mv) + ctx.params.delta_margin <= alpha
+                && !board.gives_check(mv)
+        }) {
+            continue;
+        }
+
+        ctx.play(board, mv);
+        let score = -search(board, -beta, -alpha, ply + 1, qply + 1, ctx);
+        ctx.undo(board);
+
+        if score > best_score {
+            best_score = score;
+            best_move = Some(mv);
+        }
+
         alpha = alpha.max(score);
+        if alpha >= beta {
+            break; // beta cutoff
+        }
+    }
+
+    // Store TT entry (standing pat has no move to store)
+    if let Some(best_move) = best_move {
+        let bound = if best_score <= alpha_orig {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        ctx.tt
+            .store(hash, score_to_tt(best_score, ply), 0, bound, best_move);
+    }
+
+    best_score
+}
+
+/// Moves searched by quiescence among the legal moves: every evasion when in check, otherwise
+/// captures and promotions, and optionally quiet moves giving check.
+fn noisy_moves(board: &Board, mut moves: MoveList, in_check: bool, quiet_checks: bool) -> MoveList {
+    if !in_check {
+        moves.retain(|mv| {
+            mv.is_capture() || mv.is_promotion() || quiet_checks && board.gives_check(*mv)
+        });
+    }
+    moves
+}
+
+/// Material won by a move if it is not recaptured.
+fn material_gain(mv: Move) -> i32 {
+    mv.capture().map_or(0, material_score)
+        + mv.promotion().map_or(0, |promoted| {
+            material_score(promoted) - material_score(Role::Pawn)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{params::SearchParams, transposition::FastTranspositionTable};
+    use shakmaty::{fen::Fen, CastlingMode, Chess};
+    use std::str::FromStr;
+
+    fn quiescence_of(fen_str: &str) -> i32 {
+        let pos: Chess = Fen::from_str(fen_str)
+            .unwrap()
+            .into_position(CastlingMode::Standard)
+            .unwrap();
+        let mut ctx = SearchContext::new(FastTranspositionTable::new(16), SearchParams::default());
+        quiescence(&mut Board::from(&pos), i32::MIN + 1, i32::MAX, 0, &mut ctx)
+    }
+
+    #[test]
+    fn test_detects_mate_when_in_check() {
+        assert_eq!(
+            quiescence_of("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1"),
+            i32::MIN + 1
+        );
     }
 
-    alpha
+    #[test]
+    fn test_draw_ignores_stored_score() {
+        let pos: Chess = Fen::from_str("4k3/8/8/8/8/8/8/3QK3 w - - 100 80")
+            .unwrap()
+            .into_position(CastlingMode::Standard)
+            .unwrap();
+        let mut board = Board::from(&pos);
+        let mut ctx = SearchContext::new(FastTranspositionTable::new(16), SearchParams::default());
+        let mv = board.legal_moves()[0];
+        ctx.tt.store(board.hash(), 5000, 4, Bound::Exact, mv);
+        assert_eq!(
+            quiescence(&mut board, i32::MIN + 1, i32::MAX, 0, &mut ctx),
+            0
+        );
+    }
+
+    #[test]
+    fn test_stalemate_is_a_draw() {
+        // A bare king against a queen would stand pat on a won mop-up score
+        assert_eq!(quiescence_of("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1"), 0);
+    }
+
+    #[test]
+    fn test_finds_quiet_promotion() {
+        let score = quiescence_of("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(score > material_score(Role::Queen) / 2);
+    }
 }
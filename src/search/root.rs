@@ -0,0 +1,87 @@
+use crate::eval::order;
+use shakmaty::{Chess, Move, Position};
+
+/// A legal move at the root with what was learned about it in earlier iterations.
+#[derive(Clone, Debug)]
+pub struct RootMove {
+    pub mv: Move,
+    // Fail-soft score from the most recent search of the move
+    pub score: i32,
+    // Nodes spent on the subtree of the move in the most recent search
+    pub nodes: u64,
+}
+
+/// The moves at the root, kept in the order they are searched across iterations.
+pub struct RootMoves {
+    pub moves: Vec<RootMove>,
+}
+
+impl RootMoves {
+    /// Collects the legal moves of a position, with the given move first if it is legal and the
+    /// rest ordered by `order::order`.
+    pub fn new(position: &Chess, first: Option<Move>) -> Self {
+        let mut moves = position.legal_moves();
+
+        let mut order_start_index = 0;
+        if let Some(first) = first {
+            if let Some(i) = moves.iter().position(|m| m == &first) {
+                moves.swap(0, i);
+                order_start_index = 1;
+            }
+        }
+
+        RootMoves {
+            moves: order::order(moves, order_start_index, position.board())
+                .into_iter()
+                .map(|mv| RootMove {
+                    mv,
+                    score: i32::MIN + 1,
+                    nodes: 0,
+                })
+                .collect(),
+        }
+    }
+
+    /// Orders the moves for the next search: the best move first, then the rest by the size of
+    /// their subtrees, since moves that took long to refute are the likeliest to become best.
+    pub fn sort(&mut self, best: Move) {
+        self.moves.sort_by(|a, b| {
+            (b.mv == best)
+                .cmp(&(a.mv == best))
+                .then(b.nodes.cmp(&a.nodes))
+                .then(b.score.cmp(&a.score))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_move_is_kept_first() {
+        let pos = Chess::default();
+        let last = *pos.legal_moves().last().unwrap();
+
+        let root_moves = RootMoves::new(&pos, Some(last));
+        assert_eq!(root_moves.moves.len(), 20);
+        assert_eq!(root_moves.moves[0].mv, last);
+    }
+
+    #[test]
+    fn test_sort_by_best_then_nodes() {
+        let pos = Chess::default();
+        let mut root_moves = RootMoves::new(&pos, None);
+        for (i, root_move) in root_moves.moves.iter_mut().enumerate() {
+            root_move.nodes = i as u64;
+        }
+        let best = root_moves.moves[3].mv;
+        let largest = root_moves.moves[19].mv;
+        let smallest = root_moves.moves[0].mv;
+
+        root_moves.sort(best);
+        assert_eq!(root_moves.moves[0].mv, best);
+        assert_eq!(root_moves.moves[1].mv, largest);
+        assert_eq!(root_moves.moves[19].mv, smallest);
+    }
+}
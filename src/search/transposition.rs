@@ -1,10 +1,10 @@
-use shakmaty::{zobrist::Zobrist64, Chess, EnPassantMode, Move, Position};
+use crate::search::MATE_THRESHOLD;
+use shakmaty::{zobrist::Zobrist64, Move};
 
 pub trait TranspositionTable {
     fn lookup(&self, key: Zobrist64) -> Option<TTEntry>;
     fn store(&mut self, key: Zobrist64, score: i32, depth: u8, bound: Bound, best_move: Move);
     fn best_move(&self, key: Zobrist64) -> Option<Move>;
-    fn pv(&self, pos: Chess, best_move: Option<Move>, depth: u8) -> Vec<Move>;
     fn clear(&mut self);
 }
 
@@ -23,6 +23,39 @@ pub struct TTEntry {
     pub best_move: Move,
 }
 
+impl TTEntry {
+    /// Converts the stored score back to one relative to the root, for the node at `ply`.
+    pub fn at_ply(self, ply: u8) -> TTEntry {
+        TTEntry {
+            score: score_from_tt(self.score, ply),
+            ..self
+        }
+    }
+}
+
+/// Converts a mate score relative to the root into one relative to the node at `ply`, so it keeps
+/// the right distance when the position is reached again at another ply.
+pub fn score_to_tt(score: i32, ply: u8) -> i32 {
+    if score >= MATE_THRESHOLD {
+        score + ply as i32
+    } else if score <= -MATE_THRESHOLD {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Converts a mate score stored relative to its node back into one relative to the root.
+pub fn score_from_tt(score: i32, ply: u8) -> i32 {
+    if score >= MATE_THRESHOLD {
+        score - ply as i32
+    } else if score <= -MATE_THRESHOLD {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
 pub struct FastTranspositionTable {
     table: Vec<Option<(Zobrist64, TTEntry)>>,
     size_power: u8,
@@ -58,6 +91,16 @@ impl TranspositionTable for FastTranspositionTable {
     #[inline(always)]
     fn store(&mut self, key: Zobrist64, score: i32, depth: u8, bound: Bound, best_move: Move) {
         let index = self.index(key);
+
+        // Quiescence results must not throw away a deeper search of the same position
+        if depth == 0 {
+            if let Some((zobrist, entry)) = &self.table[index] {
+                if *zobrist == key && entry.depth > 0 {
+                    return;
+                }
+            }
+        }
+
         self.table[index] = Some((
             key,
             TTEntry {
@@ -81,31 +124,43 @@ impl TranspositionTable for FastTranspositionTable {
         None
     }
 
-    #[inline(always)]
-    fn pv(&self, mut pos: Chess, mut best_move: Option<Move>, mut depth: u8) -> Vec<Move> {
-        let mut pv = Vec::new();
-
-        while let Some(mv) = best_move {
-            // Add move to pv
-            pv.push(mv.clone());
-
-            // Update position by playing move
-            pos.play_unchecked(mv);
-            depth -= 1;
-
-            // Stop if depth has been reached
-            if depth == 0 {
-                break;
-            }
-
-            // Find best move at new position
-            best_move = self.best_move(pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal));
-        }
+    fn clear(&mut self) {
+        self.table = vec![None; 1 << self.size_power]
+    }
+}
 
-        pv
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::{Chess, EnPassantMode, Position};
+
+    #[test]
+    fn test_quiescence_entry_keeps_deeper_entry() {
+        let pos = Chess::default();
+        let key: Zobrist64 = pos.zobrist_hash(EnPassantMode::Legal);
+        let mv = pos.legal_moves()[0];
+        let mut tt = FastTranspositionTable::new(8);
+
+        tt.store(key, 10, 3, Bound::Exact, mv);
+        tt.store(key, 20, 0, Bound::Lower, mv);
+        assert_eq!(tt.lookup(key).unwrap().depth, 3);
+
+        // Other positions and searches of any depth replace quiescence results
+        tt.store(Zobrist64(key.0 ^ 1), 30, 0, Bound::Exact, mv);
+        tt.store(Zobrist64(key.0 ^ 1), 40, 1, Bound::Exact, mv);
+        assert_eq!(tt.lookup(Zobrist64(key.0 ^ 1)).unwrap().score, 40);
     }
 
-    fn clear(&mut self) {
-        self.table = vec![None; 1 << self.size_power]
+    #[test]
+    fn test_mate_scores_are_stored_relative_to_node() {
+        // Mating at ply 5, found from a node at ply 3, is a mate in 2 plies from that node
+        let mate = i32::MAX - 5;
+        assert_eq!(score_to_tt(mate, 3), i32::MAX - 2);
+        // Reached again at ply 1, the mate is 3 plies from the root
+        assert_eq!(score_from_tt(score_to_tt(mate, 3), 1), i32::MAX - 3);
+
+        let mated = i32::MIN + 1 + 5;
+        assert_eq!(score_from_tt(score_to_tt(mated, 3), 1), i32::MIN + 1 + 3);
+        assert_eq!(score_to_tt(150, 3), 150);
     }
 }
@@ -1,40 +1,37 @@
-use shakmaty::{fen::Fen, zobrist::Zobrist64, CastlingMode, Chess, EnPassantMode, Move, Position};
+use shakmaty::{fen::Fen, san::San, CastlingMode, Chess, Move, Position};
+use skakarlak::board::Board;
 use skakarlak::search::{
-    negamax::negamax,
-    transposition::{FastTranspositionTable, TranspositionTable},
+    context::SearchContext, negamax::negamax, params::SearchParams,
+    transposition::FastTranspositionTable,
 };
 
 use std::str::FromStr;
 
 fn find_mate(pos: Chess, in_n_moves: u8) -> Vec<Move> {
-    let mut tt = FastTranspositionTable::new(25);
+    let mut ctx = SearchContext::new(FastTranspositionTable::new(25), SearchParams::default());
     let ply = in_n_moves * 2 - 1;
-    let mut nodes = 0;
-    let hash = pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal);
-    let score = negamax(
-        &pos,
-        ply,
-        i32::MIN + 1,
-        i32::MAX,
-        0,
-        &mut tt,
-        &mut nodes,
-        hash,
-    );
-
-    let pv = tt.pv(pos.clone(), tt.best_move(hash), ply as u8);
+    let mut board = Board::from(&pos);
+    let score = negamax(&mut board, ply, i32::MIN + 1, i32::MAX, 0, &mut ctx);
+
+    let pv = ctx.pv.line(0);
 
     assert_eq!(score, i32::MAX - ply as i32);
     assert_eq!(pv.len(), ply as usize);
 
     let mut test_pos = pos.clone();
     for mv in &pv {
-        test_pos = test_pos.play(mv.clone()).unwrap();
+        test_pos = test_pos.play(*mv).unwrap();
     }
     assert!(test_pos.is_checkmate());
     pv
 }
 
+/// Checks that the line found starts with the known solution of the problem.
+fn assert_solution(pos: &Chess, pv: Vec<Move>, solution: &str) {
+    let solution = San::from_str(solution).unwrap().to_move(pos).unwrap();
+    assert_eq!(pv[0], solution);
+}
+
 fn parse_fen(fen_str: &str) -> Chess {
     Fen::from_str(fen_str)
         .unwrap()
@@ -179,3 +176,21 @@ fn test_mate_in_3_blackburne_anderssen_1873() {
     let pos = parse_fen("Q4R2/3kr3/1q3n1p/2p1p1p1/1p1bP1P1/1B1P3P/2PBK3/8 w - - 1 0");
     find_mate(pos, 3);
 }
+
+// Harder forcing mates, from the finishes of classic games with a single solution
+
+#[test]
+// Adolf Anderssen vs Jean Dufresne, Berlin, 1852 (the Evergreen Game)
+// 1. Qxd7+ Kxd7 2. Bf5+ Ke8 3. Bd7+ Kf8 4. Bxe7#
+fn test_mate_in_4_anderssen_dufresne_1852() {
+    let pos = parse_fen("1r2k1r1/pbppnp1p/1b3P2/8/Q7/B1PB1q2/P4PPP/3R2K1 w - - 0 21");
+    assert_solution(&pos, find_mate(pos.clone(), 4), "Qxd7+");
+}
+
+#[test]
+// Edward Lasker vs George Alan Thomas, London, 1912
+// 1. Neg4+ Kg5 2. f4+ Kh4 3. g3+ Kh3 4. Bf1+ Bg2 5. Nf2#
+fn test_mate_in_5_lasker_thomas_1912() {
+    let pos = parse_fen("rn3r2/pbppq1p1/1p2pN1k/4N3/3P4/3B4/PPP2PPP/R3K2R w KQ - 1 13");
+    assert_solution(&pos, find_mate(pos.clone(), 5), "Neg4+");
+}
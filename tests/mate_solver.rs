@@ -0,0 +1,157 @@
+use shakmaty::{fen::Fen, san::San, CastlingMode, Chess, Position};
+use skakarlak::search::mate::{solve, MateResult, Solution};
+
+use std::str::FromStr;
+
+const MAX_NODES: usize = 2_000_000;
+
+// Mates in 3, source: https://wtharvey.com/m8n3.txt
+const MATES_IN_3: &[&str] = &[
+    "r1b1kb1r/pppp1ppp/5q2/4n3/3KP3/2N3PN/PPP4P/R1BQ1B1R b kq - 0 1",
+    "r3k2r/ppp2Npp/1b5n/4p2b/2B1P2q/BQP2P2/P5PP/RN5K w kq - 1 0",
+    "r1b3kr/ppp1Bp1p/1b6/n2P4/2p3q1/2Q2N2/P4PPP/RN2R1K1 w - - 1 0",
+    "r2n1rk1/1ppb2pp/1p1p4/3Ppq1n/2B3P1/2P4P/PP1N1P1K/R2Q1RN1 b - - 0 1",
+    "3q1r1k/2p4p/1p1pBrp1/p2Pp3/2PnP3/5PP1/PP1Q2K1/5R1R w - - 1 0",
+    "6k1/ppp2ppp/8/2n2K1P/2P2P1P/2Bpr3/PP4r1/4RR2 b - - 0 1",
+    "rn3rk1/p5pp/2p5/3Ppb2/2q5/1Q6/PPPB2PP/R3K1NR b - - 0 1",
+    "N1bk4/pp1p1Qpp/8/2b5/3n3q/8/PPP2RPP/RNB1rBK1 b - - 0 1",
+    "8/2p3N1/6p1/5PB1/pp2Rn2/7k/P1p2K1P/3r4 w - - 1 0",
+    "r1b1k1nr/p2p1ppp/n2B4/1p1NPN1P/6P1/3P1Q2/P1P1K3/q5b1 w - - 1 0",
+    "1q2r3/k4p2/prQ2b1p/R7/1PP1B1p1/6P1/P5K1/8 w - - 1 0",
+    "r1bqr1k1/ppp2pp1/3p4/4n1NQ/2B1PN2/8/P4PPP/b4RK1 w - - 1 0",
+    "3r4/pp5Q/B7/k7/3q4/2b5/P4PPP/1R4K1 w - - 1 0",
+    "rnbk1b1r/ppqpnQ1p/4p1p1/2p1N1B1/4N3/8/PPP2PPP/R3KB1R w - - 1 0",
+    "3rnr1k/p1q1b1pB/1pb1p2p/2p1P3/2P2N2/PP4P1/1BQ4P/4RRK1 w - - 1 0",
+    "k7/1p1rr1pp/pR1p1p2/Q1pq4/P7/8/2P3PP/1R4K1 w - - 1 0",
+    "Q4R2/3kr3/1q3n1p/2p1p1p1/1p1bP1P1/1B1P3P/2PBK3/8 w - - 1 0",
+];
+
+/// A composed or game-derived mate with its known length and key move.
+struct MateProblem {
+    name: &'static str,
+    moves: u8,
+    fen: &'static str,
+    // Key move in SAN, the only one mating in time
+    solution: &'static str,
+}
+
+// Longer mates from the finishes of classic games
+const LONGER_MATES: &[MateProblem] = &[
+    MateProblem {
+        // 1. Qxd7+ Kxd7 2. Bf5+ Ke8 3. Bd7+ Kf8 4. Bxe7#
+        name: "Anderssen vs Dufresne, Berlin, 1852",
+        moves: 4,
+        fen: "1r2k1r1/pbppnp1p/1b3P2/8/Q7/B1PB1q2/P4PPP/3R2K1 w - - 0 21",
+        solution: "Qxd7+",
+    },
+    MateProblem {
+        // 1... Rh3 2. Rf3 Bxf3 3. Bd4 Bxd4 4. Qxf3 Rxh2#
+        name: "Rotlewi vs Rubinstein, Lodz, 1907, after 25. Qg2",
+        moves: 4,
+        fen: "6k1/5ppp/pb2p3/1p2P3/1P2bPnP/P1r5/1B4QP/R4R1K b - - 1 25",
+        solution: "Rh3",
+    },
+    MateProblem {
+        // 1... Bxe4+ 2. Qg2 Rh3 3. Rf3 Bxf3 4. Bd4 Bxd4 5. Qxf3 Rxh2#
+        name: "Rotlewi vs Rubinstein, Lodz, 1907, after 24. Qxd2",
+        moves: 5,
+        fen: "6k1/1b3ppp/pb2p3/1p2P3/1P2BPnP/P1r5/1B1Q3P/R4R1K b - - 0 24",
+        solution: "Bxe4+",
+    },
+    MateProblem {
+        // 1. Neg4+ Kg5 2. f4+ Kh4 3. g3+ Kh3 4. Bf1+ Bg2 5. Nf2#
+        name: "Lasker vs Thomas, London, 1912",
+        moves: 5,
+        fen: "rn3r2/pbppq1p1/1p2pN1k/4N3/3P4/3B4/PPP2PPP/R3K2R w KQ - 1 13",
+        solution: "Neg4+",
+    },
+];
+
+fn longer_mate(name: &str) -> &'static MateProblem {
+    LONGER_MATES
+        .iter()
+        .find(|problem| problem.name == name)
+        .unwrap()
+}
+
+fn parse_fen(fen_str: &str) -> Chess {
+    Fen::from_str(fen_str)
+        .unwrap()
+        .into_position(CastlingMode::Standard)
+        .unwrap()
+}
+
+/// Checks that a solution answers every defence and always ends in checkmate.
+fn verify(pos: &Chess, solution: &Solution) {
+    let pos = pos.clone().play(solution.mv).unwrap();
+    if solution.defences.is_empty() {
+        assert!(pos.is_checkmate());
+        return;
+    }
+
+    let defences = pos.legal_moves();
+    assert_eq!(solution.defences.len(), defences.len());
+    for (defence, continuation) in &solution.defences {
+        assert!(defences.contains(defence));
+        verify(&pos.clone().play(*defence).unwrap(), continuation);
+    }
+}
+
+fn assert_mate_in(fen_str: &str, moves: u8) -> Solution {
+    let pos = parse_fen(fen_str);
+
+    let MateResult::Mate(solution) = solve(&pos, moves, MAX_NODES, || false).0 else {
+        panic!("no mate in {moves} found: {fen_str}");
+    };
+    assert_eq!(solution.moves(), moves, "{fen_str}");
+    assert_eq!(solution.main_line().len(), moves as usize * 2 - 1);
+    verify(&pos, &solution);
+
+    // The mate is the shortest one
+    assert_eq!(
+        solve(&pos, moves - 1, MAX_NODES, || false).0,
+        MateResult::NoMate,
+        "{fen_str}"
+    );
+    solution
+}
+
+#[test]
+fn test_mates_in_3() {
+    for fen_str in MATES_IN_3 {
+        assert_mate_in(fen_str, 3);
+    }
+}
+
+#[test]
+fn test_longer_mates() {
+    for problem in LONGER_MATES {
+        let solution = assert_mate_in(problem.fen, problem.moves);
+        let pos = parse_fen(problem.fen);
+        let key = San::from_str(problem.solution)
+            .unwrap()
+            .to_move(&pos)
+            .unwrap();
+        assert_eq!(solution.mv, key, "{}", problem.name);
+    }
+}
+
+#[test]
+fn test_mate_found_with_spare_moves() {
+    let pos = parse_fen(MATES_IN_3[0]);
+    let MateResult::Mate(solution) = solve(&pos, 5, MAX_NODES, || false).0 else {
+        panic!("no mate found");
+    };
+    assert!(solution.moves() <= 5);
+    verify(&pos, &solution);
+}
+
+#[test]
+fn test_interrupted() {
+    let problem = longer_mate("Lasker vs Thomas, London, 1912");
+    let pos = parse_fen(problem.fen);
+    assert_eq!(
+        solve(&pos, problem.moves, MAX_NODES, || true).0,
+        MateResult::Unknown
+    );
+}
@@ -0,0 +1,130 @@
+use shakmaty::{fen::Fen, CastlingMode, Chess, EnPassantMode, Move, Position};
+use skakarlak::{board::Board, eval::accumulator::Accumulator};
+
+use std::str::FromStr;
+
+// Perft positions with known node counts, source: https://www.chessprogramming.org/Perft_Results
+const PERFT_RESULTS: &[(&str, u8, u64)] = &[
+    (
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        4,
+        197_281,
+    ),
+    (
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        3,
+        97_862,
+    ),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 5, 674_624),
+    (
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        3,
+        9_467,
+    ),
+    (
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        3,
+        62_379,
+    ),
+];
+
+const FENS: &str = include_str!("../assets/fens.txt");
+
+fn parse_fen(fen_str: &str) -> Chess {
+    Fen::from_str(fen_str)
+        .unwrap()
+        .into_position(CastlingMode::Standard)
+        .unwrap()
+}
+
+fn perft(board: &mut Board, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut nodes = 0;
+    for mv in board.legal_moves() {
+        board.play(mv);
+        nodes += perft(board, depth - 1);
+        board.undo();
+    }
+    nodes
+}
+
+/// Walks the tree of both representations in lockstep, comparing moves, hashes, checks and
+/// evaluation terms at every node. Moves of the parent are used as candidates for the legality
+/// test.
+fn compare(board: &mut Board, pos: &Chess, candidates: &[Move], depth: u8) {
+    let mut moves = board.legal_moves().to_vec();
+    let mut expected = pos.legal_moves().to_vec();
+    let key = |mv: &Move| mv.to_string();
+    moves.sort_by_key(key);
+    expected.sort_by_key(key);
+
+    assert_eq!(
+        moves,
+        expected,
+        "{}",
+        Fen::from_position(pos, EnPassantMode::Legal)
+    );
+    assert_eq!(board.hash(), pos.zobrist_hash(EnPassantMode::Legal));
+    assert_eq!(board.is_check(), pos.is_check());
+    assert_eq!(*board.accumulator(), Accumulator::new(pos.board()));
+    assert_eq!(board.pawn_hash(), Board::from(pos).pawn_hash());
+    assert_eq!(board.is_game_over(), pos.is_game_over());
+    let mut captures = board.capture_moves().to_vec();
+    let mut expected_captures = pos.capture_moves().to_vec();
+    captures.sort_by_key(key);
+    expected_captures.sort_by_key(key);
+    assert_eq!(captures, expected_captures);
+    for mv in candidates.iter().chain(&expected) {
+        assert_eq!(board.is_legal(*mv), pos.is_legal(*mv), "{mv}");
+    }
+
+    if depth == 0 {
+        return;
+    }
+
+    for mv in &expected {
+        let mut child = pos.clone();
+        child.play_unchecked(*mv);
+        assert_eq!(board.gives_check(*mv), child.is_check(), "{mv}");
+        board.play(*mv);
+        compare(board, &child, &expected, depth - 1);
+        board.undo();
+    }
+    assert_eq!(board.to_chess(), *pos);
+}
+
+#[test]
+fn test_perft_results() {
+    for (fen_str, depth, nodes) in PERFT_RESULTS {
+        let mut board = Board::from(&parse_fen(fen_str));
+        assert_eq!(perft(&mut board, *depth), *nodes, "{fen_str}");
+    }
+}
+
+#[test]
+fn test_matches_shakmaty_on_perft_results() {
+    for (fen_str, _, _) in PERFT_RESULTS {
+        let pos = parse_fen(fen_str);
+        compare(&mut Board::from(&pos), &pos, &[], 2);
+    }
+}
+
+#[test]
+fn test_matches_shakmaty_on_fens() {
+    for fen_str in FENS.lines().step_by(10) {
+        let pos = parse_fen(fen_str);
+        compare(&mut Board::from(&pos), &pos, &[], 1);
+    }
+}
+
+#[test]
+fn test_perft_matches_shakmaty_on_fens() {
+    for fen_str in FENS.lines().step_by(50) {
+        let pos = parse_fen(fen_str);
+        let mut board = Board::from(&pos);
+        assert_eq!(perft(&mut board, 3), shakmaty::perft(&pos, 3), "{fen_str}");
+    }
+}